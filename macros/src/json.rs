@@ -0,0 +1,167 @@
+// `fmt = json` renders messages as JSON objects instead of text
+
+use syn::{Field, Type};
+
+pub(crate) enum JsonValue {
+    // Integers and booleans are valid JSON as is
+    Raw,
+    // Other values are rendered via `Display` or `Debug` and escaped at runtime
+    Display,
+    Debug,
+    Redacted,
+}
+
+impl JsonValue {
+    pub(crate) fn derive(field: &Field, debug: bool, sensitive: bool) -> Self {
+        if sensitive {
+            JsonValue::Redacted
+        } else if debug {
+            JsonValue::Debug
+        } else if is_raw(&field.ty) {
+            JsonValue::Raw
+        } else {
+            JsonValue::Display
+        }
+    }
+
+    // Wrapper from `justerror::__private` applied to the format argument
+    pub(crate) fn wrapper(&self) -> Option<&'static str> {
+        match self {
+            JsonValue::Raw | JsonValue::Redacted => None,
+            JsonValue::Display => Some("JsonDisplay"),
+            JsonValue::Debug => Some("JsonDebug"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct JsonOutput {
+    type_name: String,
+    variant: Option<String>,
+    reason: bool,
+    code: Option<u32>,
+    desc: Vec<String>,
+    category: Option<String>,
+    help: Option<String>,
+    url: Option<String>,
+    fields: Vec<String>,
+}
+
+impl JsonOutput {
+    pub(crate) fn push_code(&mut self, code: u32) {
+        self.code = Some(code);
+    }
+
+    pub(crate) fn push_title(&mut self, type_name: String, variant: Option<String>) {
+        self.type_name = type_name;
+        self.variant = variant;
+    }
+
+    pub(crate) fn push_reason(&mut self) {
+        self.reason = true;
+    }
+
+    pub(crate) fn push_desc(&mut self, desc: &str) {
+        self.desc.push(desc.to_owned());
+    }
+
+    pub(crate) fn push_category(&mut self, category: &str) {
+        self.category = Some(category.to_owned());
+    }
+
+    pub(crate) fn push_help(&mut self, help: &str) {
+        self.help = Some(help.to_owned());
+    }
+
+    pub(crate) fn push_url(&mut self, url: String) {
+        self.url = Some(url);
+    }
+
+    pub(crate) fn push_field(&mut self, label: &str, value: &JsonValue) {
+        let value = match value {
+            JsonValue::Redacted => string(crate::REDACTED),
+            JsonValue::Raw | JsonValue::Display | JsonValue::Debug => "{}".to_string(),
+        };
+
+        self.fields.push(format!("{}:{}", string(label), value));
+    }
+
+    // Format string of the whole object. `desc` and `help` may reference fields, so they are
+    // escaped for JSON only and stay format strings, like the already escaped `url`.
+    pub(crate) fn format(&self) -> String {
+        let mut entries = vec![format!("\"type\":{}", string(&self.type_name))];
+
+        if let Some(variant) = &self.variant {
+            entries.push(format!("\"variant\":{}", string(variant)));
+        }
+
+        // Rendered via a positional argument, which precedes the ones of `desc`
+        if self.reason {
+            entries.push("\"reason\":{}".to_string());
+        }
+
+        if let Some(code) = self.code {
+            entries.push(format!("\"code\":{}", code));
+        }
+
+        if !self.desc.is_empty() {
+            entries.push(format!("\"desc\":{}", string(&self.desc.join(": "))));
+        }
+
+        if let Some(category) = &self.category {
+            entries.push(format!(
+                "\"category\":{}",
+                string(&category.replace('{', "{{").replace('}', "}}"))
+            ));
+        }
+
+        if let Some(help) = &self.help {
+            entries.push(format!("\"help\":{}", string(help)));
+        }
+
+        if let Some(url) = &self.url {
+            entries.push(format!("\"url\":{}", string(url)));
+        }
+
+        if !self.fields.is_empty() {
+            entries.push(format!("\"fields\":{{{{{}}}}}", self.fields.join(",")));
+        }
+
+        format!("{{{{{}}}}}", entries.join(","))
+    }
+}
+
+fn string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+
+    result.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            char if char.is_control() => result.push_str(&format!("\\u{:04x}", char as u32)),
+            char => result.push(char),
+        }
+    }
+    result.push('"');
+
+    result
+}
+
+fn is_raw(ty: &Type) -> bool {
+    const RAW_TYPES: &[&str] = &[
+        "bool", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+        "usize",
+    ];
+
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => ty
+            .path
+            .get_ident()
+            .is_some_and(|ident| RAW_TYPES.iter().any(|raw| ident == raw)),
+        _ => false,
+    }
+}
@@ -0,0 +1,4450 @@
+//! Procedural macro behind [`justerror`](https://docs.rs/justerror). Depend on `justerror` instead of this crate.
+
+extern crate proc_macro;
+
+mod json;
+mod miette;
+mod standalone;
+mod tracing;
+mod validate;
+
+use std::{
+    collections::HashMap,
+    default::Default,
+    fmt::{self, Display},
+};
+
+use json::{JsonOutput, JsonValue};
+use miette::Diagnostic;
+use proc_macro::TokenStream as CompilerTokenStream;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream, Parser},
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Data, DeriveInput, Error as SyntaxError, Field, Fields, Generics, Ident, Lit,
+    LitChar, LitInt, LitStr, Member, Meta, MetaNameValue, Path, Token, Type, Variant, Visibility,
+    WhereClause,
+};
+
+const DEFAULT_HEADER: &str = "=== ↴";
+const REDACTED: &str = "[REDACTED]";
+
+const ERROR_ATTR: &str = "error";
+const FMT_ATTR: &str = "fmt";
+const FROM_ATTR: &str = "from";
+const BACKTRACE_ATTR: &str = "backtrace";
+const SKIP_ATTR: &str = "skip";
+const SENSITIVE_ATTR: &str = "sensitive";
+const DEFAULT_ATTR: &str = "default";
+const LABEL_ATTR: &str = "label";
+const REASON_ATTR: &str = "reason";
+const DOC_ATTR: &str = "doc";
+const SOURCE_ATTR: &str = "source";
+const JUSTERROR_ATTR: &str = "justerror";
+const CONTEXT_FIELD: &str = "context";
+
+mod kw {
+    syn::custom_keyword!(desc);
+    syn::custom_keyword!(fmt);
+    syn::custom_keyword!(debug);
+    syn::custom_keyword!(display);
+    syn::custom_keyword!(transparent);
+    syn::custom_keyword!(header);
+    syn::custom_keyword!(compact);
+    syn::custom_keyword!(title);
+    syn::custom_keyword!(no_title);
+    syn::custom_keyword!(code);
+    syn::custom_keyword!(help);
+    syn::custom_keyword!(url);
+    syn::custom_keyword!(predicates);
+    syn::custom_keyword!(accessors);
+    syn::custom_keyword!(derives);
+    syn::custom_keyword!(result_alias);
+    syn::custom_keyword!(truncate);
+    syn::custom_keyword!(no_debug);
+    syn::custom_keyword!(debug_same_as_display);
+    syn::custom_keyword!(all_fields_debug);
+    syn::custom_keyword!(anyhow_compat);
+    syn::custom_keyword!(log_on_create);
+    syn::custom_keyword!(tracing_event);
+    syn::custom_keyword!(termination);
+    syn::custom_keyword!(aggregate);
+    syn::custom_keyword!(variant_name);
+    syn::custom_keyword!(prefix);
+    syn::custom_keyword!(sep);
+    syn::custom_keyword!(json);
+    syn::custom_keyword!(hex);
+    syn::custom_keyword!(upper_hex);
+    syn::custom_keyword!(octal);
+    syn::custom_keyword!(binary);
+    syn::custom_keyword!(pointer);
+    syn::custom_keyword!(nest);
+    syn::custom_keyword!(exit_code);
+    syn::custom_keyword!(send_sync);
+    syn::custom_keyword!(deprecated);
+    syn::custom_keyword!(raw);
+    syn::custom_keyword!(category);
+    syn::custom_keyword!(show_category);
+    syn::custom_keyword!(impl_display_only);
+    syn::custom_keyword!(strum_display);
+    syn::custom_keyword!(impl_std_error);
+    syn::custom_keyword!(impl_error_code);
+    syn::custom_keyword!(constructor);
+    syn::custom_keyword!(context_map);
+    syn::custom_keyword!(hash);
+    syn::custom_keyword!(separator);
+    syn::custom_keyword!(sources);
+    syn::custom_keyword!(equality);
+    syn::custom_keyword!(clone);
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(builder);
+    syn::custom_keyword!(width);
+    syn::custom_keyword!(precision);
+    syn::custom_keyword!(fill);
+    syn::custom_keyword!(align);
+    syn::custom_keyword!(left);
+    syn::custom_keyword!(right);
+    syn::custom_keyword!(center);
+}
+
+#[derive(Default)]
+struct ErrorArgs {
+    desc: Option<Desc>,
+    fmt: Option<Fmt>,
+    transparent: Option<kw::transparent>,
+    header: Option<LitStr>,
+    compact: Option<bool>,
+    title: Option<bool>,
+    no_title: Option<kw::no_title>,
+    code: Option<u32>,
+    exit_code: Option<(i32, Span)>,
+    help: Option<LitStr>,
+    prefix: Option<String>,
+    sep: Option<String>,
+    url: Option<String>,
+    predicates: Option<kw::predicates>,
+    accessors: Option<kw::accessors>,
+    derives: Option<(kw::derives, Vec<Path>)>,
+    result_alias: Option<Ident>,
+    no_debug: Option<kw::no_debug>,
+    debug_same_as_display: Option<kw::debug_same_as_display>,
+    all_fields_debug: Option<kw::all_fields_debug>,
+    anyhow_compat: Option<kw::anyhow_compat>,
+    log_on_create: Option<kw::log_on_create>,
+    tracing_event: Option<kw::tracing_event>,
+    termination: Option<kw::termination>,
+    aggregate: Option<kw::aggregate>,
+    variant_name: Option<kw::variant_name>,
+    nest: Option<kw::nest>,
+    send_sync: Option<kw::send_sync>,
+    deprecated: Option<LitStr>,
+    raw: Option<LitStr>,
+    category: Option<String>,
+    show_category: Option<kw::show_category>,
+    impl_display_only: Option<kw::impl_display_only>,
+    strum_display: Option<kw::strum_display>,
+    impl_std_error: Option<kw::impl_std_error>,
+    impl_error_code: Option<Path>,
+    constructor: Option<kw::constructor>,
+    builder: Option<kw::builder>,
+    context_map: Option<kw::context_map>,
+    hash: Option<kw::hash>,
+    separator: Option<LitStr>,
+    sources: Option<kw::sources>,
+    equality: Option<kw::equality>,
+    clone: Option<kw::clone>,
+    unknown: Vec<Ident>,
+}
+
+impl ErrorArgs {
+    fn parse_desc(input: ParseStream) -> syn::Result<Desc> {
+        let _: kw::desc = input.parse()?;
+        let _: Token![=] = input.parse()?;
+
+        if input.peek(Lit) {
+            match input.parse()? {
+                Lit::Str(str) => Ok(Desc::Lit(str)),
+                val => Err(SyntaxError::new(
+                    val.span(),
+                    "`desc` must be a string or a path to a constant",
+                )),
+            }
+        } else {
+            Ok(Desc::Const(input.parse()?))
+        }
+    }
+
+    fn parse_help(input: ParseStream) -> syn::Result<LitStr> {
+        let _: kw::help = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str),
+            _ => Err(SyntaxError::new(val.span(), "`help` must be a string")),
+        }
+    }
+
+    fn parse_prefix(input: ParseStream) -> syn::Result<String> {
+        let _: kw::prefix = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str.value()),
+            _ => Err(SyntaxError::new(val.span(), "`prefix` must be a string")),
+        }
+    }
+
+    fn parse_sep(input: ParseStream) -> syn::Result<String> {
+        let _: kw::sep = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => {
+                let sep = str.value();
+                if sep.contains(['\n', '\r', '{', '}']) {
+                    Err(SyntaxError::new(
+                        str.span(),
+                        "`sep` can't contain line breaks or braces",
+                    ))
+                } else {
+                    Ok(sep)
+                }
+            }
+            _ => Err(SyntaxError::new(val.span(), "`sep` must be a string")),
+        }
+    }
+
+    fn parse_separator(input: ParseStream) -> syn::Result<LitStr> {
+        let _: kw::separator = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) if str.value().contains(['\n', '\r']) => Err(SyntaxError::new(
+                str.span(),
+                "`separator` can't contain line breaks",
+            )),
+            Lit::Str(str) => Ok(str),
+            _ => Err(SyntaxError::new(val.span(), "`separator` must be a string")),
+        }
+    }
+
+    fn parse_url(input: ParseStream) -> syn::Result<String> {
+        let _: kw::url = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => {
+                let url = str.value();
+                if url.starts_with("http://") || url.starts_with("https://") {
+                    Ok(url)
+                } else {
+                    Err(SyntaxError::new(
+                        str.span(),
+                        "`url` must start with `http://` or `https://`",
+                    ))
+                }
+            }
+            _ => Err(SyntaxError::new(val.span(), "`url` must be a string")),
+        }
+    }
+
+    fn parse_header(input: ParseStream) -> syn::Result<LitStr> {
+        let _: kw::header = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str),
+            _ => Err(SyntaxError::new(val.span(), "`header` must be a string")),
+        }
+    }
+
+    fn parse_compact(input: ParseStream) -> syn::Result<bool> {
+        let _: kw::compact = input.parse()?;
+
+        if !input.peek(Token![=]) {
+            return Ok(true);
+        }
+
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Bool(bool) => Ok(bool.value),
+            _ => Err(SyntaxError::new(val.span(), "`compact` must be a boolean")),
+        }
+    }
+
+    fn parse_title(input: ParseStream) -> syn::Result<bool> {
+        let _: kw::title = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Bool(bool) => Ok(bool.value),
+            _ => Err(SyntaxError::new(val.span(), "`title` must be a boolean")),
+        }
+    }
+
+    fn parse_code(input: ParseStream) -> syn::Result<u32> {
+        let _: kw::code = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Int(int) => int.base10_parse(),
+            _ => Err(SyntaxError::new(val.span(), "`code` must be an integer")),
+        }
+    }
+
+    fn parse_exit_code(input: ParseStream) -> syn::Result<(i32, Span)> {
+        let _: kw::exit_code = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Int(int) => Ok((int.base10_parse()?, int.span())),
+            _ => Err(SyntaxError::new(
+                val.span(),
+                "`exit_code` must be an integer",
+            )),
+        }
+    }
+
+    fn parse_deprecated(input: ParseStream) -> syn::Result<LitStr> {
+        let _: kw::deprecated = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str),
+            _ => Err(SyntaxError::new(
+                val.span(),
+                "`deprecated` must be a string",
+            )),
+        }
+    }
+
+    fn parse_raw(input: ParseStream) -> syn::Result<LitStr> {
+        let _: kw::raw = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str),
+            _ => Err(SyntaxError::new(val.span(), "`raw` must be a string")),
+        }
+    }
+
+    fn parse_category(input: ParseStream) -> syn::Result<String> {
+        let _: kw::category = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => Ok(str.value()),
+            _ => Err(SyntaxError::new(val.span(), "`category` must be a string")),
+        }
+    }
+
+    fn parse_derives(input: ParseStream) -> syn::Result<(kw::derives, Vec<Path>)> {
+        let kw: kw::derives = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let paths = Punctuated::<Path, Token![,]>::parse_terminated(&content)?;
+
+        Ok((kw, paths.into_iter().collect()))
+    }
+
+    fn parse_result_alias(input: ParseStream) -> syn::Result<Ident> {
+        let _: kw::result_alias = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => str.parse(),
+            _ => Err(SyntaxError::new(
+                val.span(),
+                "`result_alias` must be a string",
+            )),
+        }
+    }
+
+    fn parse_impl_error_code(input: ParseStream) -> syn::Result<Path> {
+        let _: kw::impl_error_code = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let val: Lit = input.parse()?;
+
+        match val {
+            Lit::Str(str) => str.parse(),
+            _ => Err(SyntaxError::new(
+                val.span(),
+                "`impl_error_code` must be a string with the path to the trait",
+            )),
+        }
+    }
+
+    // Points to the duplicate and to the first definition of the argument
+    fn check_duplicate(
+        defined: &mut Vec<(&'static str, Span)>,
+        name: &'static str,
+        span: Span,
+    ) -> syn::Result<()> {
+        match defined.iter().find(|(defined, _)| *defined == name) {
+            Some((_, first_span)) => {
+                let mut err = SyntaxError::new(span, format!("`{}` is already defined", name));
+                err.combine(SyntaxError::new(
+                    *first_span,
+                    format!("`{}` is first defined here", name),
+                ));
+                Err(err)
+            }
+            None => {
+                defined.push((name, span));
+                Ok(())
+            }
+        }
+    }
+
+    fn parse_fmt(input: ParseStream) -> syn::Result<Fmt> {
+        let _: kw::fmt = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let span = input.span();
+        let val = input.parse::<Fmt>()?;
+
+        match val {
+            Fmt::Skip => Err(SyntaxError::new(
+                span,
+                "`skip` is only supported at the field level, e.g. `#[fmt(skip)]`",
+            )),
+            val => Ok(val),
+        }
+    }
+}
+
+impl ErrorArgs {
+    fn check_variant_level(&self) -> syn::Result<()> {
+        if let Some(header) = &self.header {
+            return Err(SyntaxError::new(
+                header.span(),
+                "`header` is only supported at the root level",
+            ));
+        }
+
+        if let Some(predicates) = &self.predicates {
+            return Err(SyntaxError::new(
+                predicates.span,
+                "`predicates` is only supported at the root level",
+            ));
+        }
+
+        if let Some(accessors) = &self.accessors {
+            return Err(SyntaxError::new(
+                accessors.span,
+                "`accessors` is only supported at the root level",
+            ));
+        }
+
+        if let Some((derives, _)) = &self.derives {
+            return Err(SyntaxError::new(
+                derives.span,
+                "`derives` is only supported at the root level",
+            ));
+        }
+
+        if let Some(result_alias) = &self.result_alias {
+            return Err(SyntaxError::new(
+                result_alias.span(),
+                "`result_alias` is only supported at the root level",
+            ));
+        }
+
+        if let Some(no_title) = &self.no_title {
+            return Err(SyntaxError::new(
+                no_title.span,
+                "`no_title` is only supported at the root level, use `title = false` instead",
+            ));
+        }
+
+        if let Some(no_debug) = &self.no_debug {
+            return Err(SyntaxError::new(
+                no_debug.span,
+                "`no_debug` is only supported at the root level",
+            ));
+        }
+
+        if let Some(debug_same_as_display) = &self.debug_same_as_display {
+            return Err(SyntaxError::new(
+                debug_same_as_display.span,
+                "`debug_same_as_display` is only supported at the root level",
+            ));
+        }
+
+        if let Some(all_fields_debug) = &self.all_fields_debug {
+            return Err(SyntaxError::new(
+                all_fields_debug.span,
+                "`all_fields_debug` is only supported at the root level",
+            ));
+        }
+
+        if let Some(anyhow_compat) = &self.anyhow_compat {
+            return Err(SyntaxError::new(
+                anyhow_compat.span,
+                "`anyhow_compat` is only supported at the root level",
+            ));
+        }
+
+        if let Some(variant_name) = &self.variant_name {
+            return Err(SyntaxError::new(
+                variant_name.span,
+                "`variant_name` is only supported at the root level",
+            ));
+        }
+
+        if let Some(nest) = &self.nest {
+            return Err(SyntaxError::new(
+                nest.span,
+                "`nest` is only supported at the root level",
+            ));
+        }
+
+        if let Some(send_sync) = &self.send_sync {
+            return Err(SyntaxError::new(
+                send_sync.span,
+                "`send_sync` is only supported at the root level",
+            ));
+        }
+
+        if let Some(impl_display_only) = &self.impl_display_only {
+            return Err(SyntaxError::new(
+                impl_display_only.span,
+                "`impl_display_only` is only supported at the root level",
+            ));
+        }
+
+        if let Some(strum_display) = &self.strum_display {
+            return Err(SyntaxError::new(
+                strum_display.span,
+                "`strum_display` is only supported at the root level",
+            ));
+        }
+
+        if let Some(impl_std_error) = &self.impl_std_error {
+            return Err(SyntaxError::new(
+                impl_std_error.span,
+                "`impl_std_error` is only supported at the root level",
+            ));
+        }
+
+        if let Some(impl_error_code) = &self.impl_error_code {
+            return Err(SyntaxError::new_spanned(
+                impl_error_code,
+                "`impl_error_code` is only supported at the root level",
+            ));
+        }
+
+        if let Some(constructor) = &self.constructor {
+            return Err(SyntaxError::new(
+                constructor.span,
+                "`constructor` is only supported on structs",
+            ));
+        }
+
+        if let Some(builder) = &self.builder {
+            return Err(SyntaxError::new(
+                builder.span,
+                "`builder` is only supported on structs",
+            ));
+        }
+
+        if let Some(context_map) = &self.context_map {
+            return Err(SyntaxError::new(
+                context_map.span,
+                "`context_map` is only supported on structs",
+            ));
+        }
+
+        if let Some(hash) = &self.hash {
+            return Err(SyntaxError::new(
+                hash.span,
+                "`hash` is only supported at the root level",
+            ));
+        }
+
+        if let Some(separator) = &self.separator {
+            return Err(SyntaxError::new(
+                separator.span(),
+                "`separator` is only supported at the root level",
+            ));
+        }
+
+        if let Some(sources) = &self.sources {
+            return Err(SyntaxError::new(
+                sources.span,
+                "`sources` is only supported at the root level",
+            ));
+        }
+
+        if let Some(equality) = &self.equality {
+            return Err(SyntaxError::new(
+                equality.span,
+                "`equality` is only supported at the root level",
+            ));
+        }
+
+        if let Some(clone) = &self.clone {
+            return Err(SyntaxError::new(
+                clone.span,
+                "`clone` is only supported at the root level",
+            ));
+        }
+
+        if let Some(show_category) = &self.show_category {
+            return Err(SyntaxError::new(
+                show_category.span,
+                "`show_category` is only supported at the root level",
+            ));
+        }
+
+        if let Some(log_on_create) = &self.log_on_create {
+            return Err(SyntaxError::new(
+                log_on_create.span,
+                "`log_on_create` is only supported at the root level",
+            ));
+        }
+
+        if let Some(tracing_event) = &self.tracing_event {
+            return Err(SyntaxError::new(
+                tracing_event.span,
+                "`tracing_event` is only supported at the root level",
+            ));
+        }
+
+        if let Some(termination) = &self.termination {
+            return Err(SyntaxError::new(
+                termination.span,
+                "`termination` is only supported at the root level",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_root_level(&self) -> syn::Result<()> {
+        if let Some(log_on_create) = &self.log_on_create {
+            if !cfg!(feature = "log") {
+                return Err(SyntaxError::new(
+                    log_on_create.span,
+                    "`log_on_create` requires the `log` feature of justerror",
+                ));
+            }
+        }
+
+        if let Some(tracing_event) = &self.tracing_event {
+            if !cfg!(feature = "tracing") {
+                return Err(SyntaxError::new(
+                    tracing_event.span,
+                    "`tracing_event` requires the `tracing` feature of justerror",
+                ));
+            }
+        }
+
+        if let Some(raw) = &self.raw {
+            return Err(SyntaxError::new(
+                raw.span(),
+                "`raw` is only supported at the variant level",
+            ));
+        }
+
+        if let Some(aggregate) = &self.aggregate {
+            return Err(SyntaxError::new(
+                aggregate.span,
+                "`aggregate` is only supported at the variant level",
+            ));
+        }
+
+        if let Some(equality) = &self.equality {
+            if !cfg!(feature = "std") {
+                return Err(SyntaxError::new(
+                    equality.span,
+                    "`equality` requires the `std` feature of justerror",
+                ));
+            }
+        }
+
+        if let Some(termination) = &self.termination {
+            if !cfg!(feature = "std") {
+                return Err(SyntaxError::new(
+                    termination.span,
+                    "`termination` requires the `std` feature of justerror",
+                ));
+            }
+        }
+
+        if let (Some(_), Some((exit_code, span))) = (&self.termination, self.exit_code) {
+            check_termination_exit_code(exit_code, span)?;
+        }
+
+        if let (Some(impl_std_error), Some(_)) = (&self.impl_std_error, &self.impl_display_only) {
+            return Err(SyntaxError::new(
+                impl_std_error.span,
+                "`impl_std_error` can't be combined with `impl_display_only`",
+            ));
+        }
+
+        if let (Some(strum_display), Some(_)) = (&self.strum_display, &self.impl_display_only) {
+            return Err(SyntaxError::new(
+                strum_display.span,
+                "`strum_display` can't be combined with `impl_display_only`",
+            ));
+        }
+
+        if let (Some(sources), Some(_)) = (&self.sources, &self.impl_display_only) {
+            return Err(SyntaxError::new(
+                sources.span,
+                "`sources` can't be combined with `impl_display_only`",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_enum_level(&self) -> syn::Result<()> {
+        if let Some(constructor) = &self.constructor {
+            return Err(SyntaxError::new(
+                constructor.span,
+                "`constructor` is only supported on structs",
+            ));
+        }
+
+        if let Some(builder) = &self.builder {
+            return Err(SyntaxError::new(
+                builder.span,
+                "`builder` is only supported on structs",
+            ));
+        }
+
+        if let Some(context_map) = &self.context_map {
+            return Err(SyntaxError::new(
+                context_map.span,
+                "`context_map` is only supported on structs",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_struct_level(&self) -> syn::Result<()> {
+        if let Some(predicates) = &self.predicates {
+            return Err(SyntaxError::new(
+                predicates.span,
+                "`predicates` is only supported on enums",
+            ));
+        }
+
+        if let Some(accessors) = &self.accessors {
+            return Err(SyntaxError::new(
+                accessors.span,
+                "`accessors` is only supported on enums",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Parse for ErrorArgs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        Self::parse_args(input, false)
+    }
+}
+
+impl ErrorArgs {
+    // Unknown arguments of variants are collected and reported as warnings, so attributes
+    // written for newer versions still compile
+    fn parse_variant_args(input: ParseStream<'_>) -> syn::Result<Self> {
+        Self::parse_args(input, true)
+    }
+
+    fn parse_args(input: ParseStream<'_>, lenient: bool) -> syn::Result<Self> {
+        let mut args = Self::default();
+        let mut defined = Vec::new();
+
+        loop {
+            let lookahead = input.lookahead1();
+            let span = input.span();
+
+            if lookahead.peek(kw::desc) {
+                Self::check_duplicate(&mut defined, "desc", span)?;
+                let desc = Self::parse_desc(input)?;
+                args.desc = Some(desc);
+            } else if lookahead.peek(kw::fmt) {
+                Self::check_duplicate(&mut defined, "fmt", span)?;
+                let fmt = Self::parse_fmt(input)?;
+                args.fmt = Some(fmt);
+            } else if lookahead.peek(kw::transparent) {
+                Self::check_duplicate(&mut defined, "transparent", span)?;
+                args.transparent = Some(input.parse()?);
+            } else if lookahead.peek(kw::header) {
+                Self::check_duplicate(&mut defined, "header", span)?;
+                let header = Self::parse_header(input)?;
+                args.header = Some(header);
+            } else if lookahead.peek(kw::compact) {
+                Self::check_duplicate(&mut defined, "compact", span)?;
+                let compact = Self::parse_compact(input)?;
+                args.compact = Some(compact);
+            } else if lookahead.peek(kw::title) {
+                Self::check_duplicate(&mut defined, "title", span)?;
+                let title = Self::parse_title(input)?;
+                args.title = Some(title);
+            } else if lookahead.peek(kw::no_title) {
+                Self::check_duplicate(&mut defined, "no_title", span)?;
+                args.no_title = Some(input.parse()?);
+            } else if lookahead.peek(kw::code) {
+                Self::check_duplicate(&mut defined, "code", span)?;
+                let code = Self::parse_code(input)?;
+                args.code = Some(code);
+            } else if lookahead.peek(kw::exit_code) {
+                Self::check_duplicate(&mut defined, "exit_code", span)?;
+                let exit_code = Self::parse_exit_code(input)?;
+                args.exit_code = Some(exit_code);
+            } else if lookahead.peek(kw::help) {
+                Self::check_duplicate(&mut defined, "help", span)?;
+                let help = Self::parse_help(input)?;
+                args.help = Some(help);
+            } else if lookahead.peek(kw::prefix) {
+                Self::check_duplicate(&mut defined, "prefix", span)?;
+                let prefix = Self::parse_prefix(input)?;
+                args.prefix = Some(prefix);
+            } else if lookahead.peek(kw::sep) {
+                Self::check_duplicate(&mut defined, "sep", span)?;
+                let sep = Self::parse_sep(input)?;
+                args.sep = Some(sep);
+            } else if lookahead.peek(kw::url) {
+                Self::check_duplicate(&mut defined, "url", span)?;
+                let url = Self::parse_url(input)?;
+                args.url = Some(url);
+            } else if lookahead.peek(kw::predicates) {
+                Self::check_duplicate(&mut defined, "predicates", span)?;
+                args.predicates = Some(input.parse()?);
+            } else if lookahead.peek(kw::accessors) {
+                Self::check_duplicate(&mut defined, "accessors", span)?;
+                args.accessors = Some(input.parse()?);
+            } else if lookahead.peek(kw::derives) {
+                Self::check_duplicate(&mut defined, "derives", span)?;
+                let derives = Self::parse_derives(input)?;
+                args.derives = Some(derives);
+            } else if lookahead.peek(kw::result_alias) {
+                Self::check_duplicate(&mut defined, "result_alias", span)?;
+                let result_alias = Self::parse_result_alias(input)?;
+                args.result_alias = Some(result_alias);
+            } else if lookahead.peek(kw::no_debug) {
+                Self::check_duplicate(&mut defined, "no_debug", span)?;
+                args.no_debug = Some(input.parse()?);
+            } else if lookahead.peek(kw::debug_same_as_display) {
+                Self::check_duplicate(&mut defined, "debug_same_as_display", span)?;
+                args.debug_same_as_display = Some(input.parse()?);
+            } else if lookahead.peek(kw::all_fields_debug) {
+                Self::check_duplicate(&mut defined, "all_fields_debug", span)?;
+                args.all_fields_debug = Some(input.parse()?);
+            } else if lookahead.peek(kw::anyhow_compat) {
+                Self::check_duplicate(&mut defined, "anyhow_compat", span)?;
+                args.anyhow_compat = Some(input.parse()?);
+            } else if lookahead.peek(kw::log_on_create) {
+                Self::check_duplicate(&mut defined, "log_on_create", span)?;
+                args.log_on_create = Some(input.parse()?);
+            } else if lookahead.peek(kw::tracing_event) {
+                Self::check_duplicate(&mut defined, "tracing_event", span)?;
+                args.tracing_event = Some(input.parse()?);
+            } else if lookahead.peek(kw::termination) {
+                Self::check_duplicate(&mut defined, "termination", span)?;
+                args.termination = Some(input.parse()?);
+            } else if lookahead.peek(kw::aggregate) {
+                Self::check_duplicate(&mut defined, "aggregate", span)?;
+                args.aggregate = Some(input.parse()?);
+            } else if lookahead.peek(kw::variant_name) {
+                Self::check_duplicate(&mut defined, "variant_name", span)?;
+                args.variant_name = Some(input.parse()?);
+            } else if lookahead.peek(kw::nest) {
+                Self::check_duplicate(&mut defined, "nest", span)?;
+                args.nest = Some(input.parse()?);
+            } else if lookahead.peek(kw::send_sync) {
+                Self::check_duplicate(&mut defined, "send_sync", span)?;
+                args.send_sync = Some(input.parse()?);
+            } else if lookahead.peek(kw::deprecated) {
+                Self::check_duplicate(&mut defined, "deprecated", span)?;
+                let deprecated = Self::parse_deprecated(input)?;
+                args.deprecated = Some(deprecated);
+            } else if lookahead.peek(kw::raw) {
+                Self::check_duplicate(&mut defined, "raw", span)?;
+                let raw = Self::parse_raw(input)?;
+                args.raw = Some(raw);
+            } else if lookahead.peek(kw::show_category) {
+                Self::check_duplicate(&mut defined, "show_category", span)?;
+                args.show_category = Some(input.parse()?);
+            } else if lookahead.peek(kw::impl_display_only) {
+                Self::check_duplicate(&mut defined, "impl_display_only", span)?;
+                args.impl_display_only = Some(input.parse()?);
+            } else if lookahead.peek(kw::strum_display) {
+                Self::check_duplicate(&mut defined, "strum_display", span)?;
+                args.strum_display = Some(input.parse()?);
+            } else if lookahead.peek(kw::impl_std_error) {
+                Self::check_duplicate(&mut defined, "impl_std_error", span)?;
+                args.impl_std_error = Some(input.parse()?);
+            } else if lookahead.peek(kw::impl_error_code) {
+                Self::check_duplicate(&mut defined, "impl_error_code", span)?;
+                let path = Self::parse_impl_error_code(input)?;
+                args.impl_error_code = Some(path);
+            } else if lookahead.peek(kw::constructor) {
+                Self::check_duplicate(&mut defined, "constructor", span)?;
+                args.constructor = Some(input.parse()?);
+            } else if lookahead.peek(kw::clone) {
+                Self::check_duplicate(&mut defined, "clone", span)?;
+                args.clone = Some(input.parse()?);
+            } else if lookahead.peek(kw::equality) {
+                Self::check_duplicate(&mut defined, "equality", span)?;
+                args.equality = Some(input.parse()?);
+            } else if lookahead.peek(kw::sources) {
+                Self::check_duplicate(&mut defined, "sources", span)?;
+                args.sources = Some(input.parse()?);
+            } else if lookahead.peek(kw::separator) {
+                Self::check_duplicate(&mut defined, "separator", span)?;
+                let separator = Self::parse_separator(input)?;
+                args.separator = Some(separator);
+            } else if lookahead.peek(kw::hash) {
+                Self::check_duplicate(&mut defined, "hash", span)?;
+                args.hash = Some(input.parse()?);
+            } else if lookahead.peek(kw::context_map) {
+                Self::check_duplicate(&mut defined, "context_map", span)?;
+                args.context_map = Some(input.parse()?);
+            } else if lookahead.peek(kw::builder) {
+                Self::check_duplicate(&mut defined, "builder", span)?;
+                args.builder = Some(input.parse()?);
+            } else if lookahead.peek(kw::category) {
+                Self::check_duplicate(&mut defined, "category", span)?;
+                let category = Self::parse_category(input)?;
+                args.category = Some(category);
+            } else if lenient && input.peek(Ident::peek_any) {
+                args.unknown.push(Ident::parse_any(input)?);
+                while !input.is_empty() && !input.peek(Token![,]) {
+                    input.parse::<TokenTree>()?;
+                }
+            } else {
+                return Err(lookahead.error());
+            }
+
+            if input.is_empty() {
+                break;
+            } else {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if let Some(transparent) = &args.transparent {
+            if args.desc.is_some()
+                || args.fmt.is_some()
+                || args.header.is_some()
+                || args.compact.is_some()
+                || args.title.is_some()
+                || args.no_title.is_some()
+                || args.code.is_some()
+                || args.help.is_some()
+                || args.prefix.is_some()
+                || args.sep.is_some()
+                || args.url.is_some()
+                || args.nest.is_some()
+                || args.raw.is_some()
+                || args.aggregate.is_some()
+            {
+                return Err(SyntaxError::new(
+                    transparent.span,
+                    "`transparent` can't be combined with other arguments",
+                ));
+            }
+        }
+
+        if let (Some(debug_same_as_display), Some(_)) =
+            (&args.debug_same_as_display, &args.no_debug)
+        {
+            return Err(SyntaxError::new(
+                debug_same_as_display.span,
+                "`debug_same_as_display` can't be combined with `no_debug`",
+            ));
+        }
+
+        // `no_title` is a shorthand for `title = false`
+        if let Some(no_title) = &args.no_title {
+            if args.title.is_some() {
+                return Err(SyntaxError::new(
+                    no_title.span,
+                    "`no_title` can't be combined with `title`",
+                ));
+            }
+            args.title = Some(false);
+        }
+
+        if let Some(raw) = &args.raw {
+            if args.desc.is_some() || args.fmt.is_some() {
+                return Err(SyntaxError::new(
+                    raw.span(),
+                    "`raw` can't be combined with `desc` or `fmt`",
+                ));
+            }
+        }
+
+        if let Some(aggregate) = &args.aggregate {
+            if args.raw.is_some() || args.fmt.is_some() {
+                return Err(SyntaxError::new(
+                    aggregate.span,
+                    "`aggregate` can't be combined with `raw` or `fmt`",
+                ));
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+enum Fmt {
+    #[default]
+    Display,
+    Debug,
+    Truncate(usize),
+    Json,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    Pointer,
+    Skip,
+    Custom(String),
+}
+
+impl Fmt {
+    fn derive(root: &ErrorArgs, variant: &Option<ErrorArgs>, field: &Option<Self>) -> Self {
+        match field {
+            Some(fmt) => fmt.to_owned(),
+            None => match variant {
+                Some(ErrorArgs { fmt: Some(fmt), .. }) => fmt.to_owned(),
+                Some(_) | None => match &root.fmt {
+                    Some(fmt) => fmt.to_owned(),
+                    None => Fmt::default(),
+                },
+            },
+        }
+    }
+
+    fn parse_truncate(input: ParseStream) -> syn::Result<Self> {
+        let _: kw::truncate = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let len: LitInt = content.parse()?;
+
+        Ok(Fmt::Truncate(len.base10_parse()?))
+    }
+
+    // Fields also accept named format spec arguments, e.g. `#[fmt(width = 5, align = right)]`
+    fn parse_field(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::width)
+            || input.peek(kw::precision)
+            || input.peek(kw::fill)
+            || input.peek(kw::align)
+        {
+            Ok(Fmt::Custom(input.parse::<FmtSpec>()?.to_string()))
+        } else {
+            input.parse()
+        }
+    }
+}
+
+impl Display for Fmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // JSON values are rendered via positional arguments, skipped fields are not rendered
+            Fmt::Display | Fmt::Json | Fmt::Skip => Ok(()),
+            Fmt::Debug => write!(f, ":#?"),
+            Fmt::Truncate(len) => write!(f, ":.{}", len),
+            Fmt::LowerHex => write!(f, ":x"),
+            Fmt::UpperHex => write!(f, ":X"),
+            Fmt::Octal => write!(f, ":o"),
+            Fmt::Binary => write!(f, ":b"),
+            Fmt::Pointer => write!(f, ":p"),
+            Fmt::Custom(fmt) => write!(f, ":{}", fmt),
+        }
+    }
+}
+
+impl Parse for Fmt {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let fmt = match input.parse::<kw::debug>() {
+            Ok(_) => Fmt::Debug,
+            Err(_) => match input.parse::<kw::display>() {
+                Ok(_) => Fmt::Display,
+                Err(_) if input.peek(kw::json) => {
+                    let _: kw::json = input.parse()?;
+                    Fmt::Json
+                }
+                Err(_) if input.peek(kw::hex) => {
+                    let _: kw::hex = input.parse()?;
+                    Fmt::LowerHex
+                }
+                Err(_) if input.peek(kw::upper_hex) => {
+                    let _: kw::upper_hex = input.parse()?;
+                    Fmt::UpperHex
+                }
+                Err(_) if input.peek(kw::octal) => {
+                    let _: kw::octal = input.parse()?;
+                    Fmt::Octal
+                }
+                Err(_) if input.peek(kw::binary) => {
+                    let _: kw::binary = input.parse()?;
+                    Fmt::Binary
+                }
+                Err(_) if input.peek(kw::pointer) => {
+                    let _: kw::pointer = input.parse()?;
+                    Fmt::Pointer
+                }
+                Err(_) if input.peek(kw::skip) => {
+                    let _: kw::skip = input.parse()?;
+                    Fmt::Skip
+                }
+                Err(_) if input.peek(kw::truncate) => Fmt::parse_truncate(input)?,
+                Err(_) => match input.parse::<Lit>()? {
+                    Lit::Str(str) => Fmt::Custom(str.value()),
+                    lit => return Err(SyntaxError::new(
+                        lit.span(),
+                        "`fmt` must be either `debug`, `display`, `json`, `hex`, `upper_hex`, `octal`, `binary`, `pointer`, `skip`, `truncate(N)` or a custom string",
+                    )),
+                },
+            },
+        };
+
+        Ok(fmt)
+    }
+}
+
+#[derive(Default)]
+struct FmtSpec {
+    fill: Option<char>,
+    align: Option<char>,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+impl Parse for FmtSpec {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut spec = Self::default();
+        let mut defined = Vec::new();
+        let mut fill_span = None;
+
+        loop {
+            let lookahead = input.lookahead1();
+            let span = input.span();
+
+            if lookahead.peek(kw::width) {
+                ErrorArgs::check_duplicate(&mut defined, "width", span)?;
+                let _: kw::width = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                spec.width = Some(input.parse::<LitInt>()?.base10_parse()?);
+            } else if lookahead.peek(kw::precision) {
+                ErrorArgs::check_duplicate(&mut defined, "precision", span)?;
+                let _: kw::precision = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                spec.precision = Some(input.parse::<LitInt>()?.base10_parse()?);
+            } else if lookahead.peek(kw::fill) {
+                ErrorArgs::check_duplicate(&mut defined, "fill", span)?;
+                let _: kw::fill = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                spec.fill = Some(input.parse::<LitChar>()?.value());
+                fill_span = Some(span);
+            } else if lookahead.peek(kw::align) {
+                ErrorArgs::check_duplicate(&mut defined, "align", span)?;
+                let _: kw::align = input.parse()?;
+                let _: Token![=] = input.parse()?;
+                spec.align = Some(Self::parse_align(input)?);
+            } else {
+                return Err(lookahead.error());
+            }
+
+            if input.is_empty() {
+                break;
+            } else {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        // Without alignment, only `0` is a valid fill, which is the zero padding flag
+        match (spec.fill, spec.align, fill_span) {
+            (Some(fill), None, Some(span)) if fill != '0' => Err(SyntaxError::new(
+                span,
+                "`fill` other than '0' requires `align`",
+            )),
+            _ => Ok(spec),
+        }
+    }
+}
+
+impl FmtSpec {
+    fn parse_align(input: ParseStream) -> syn::Result<char> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::left) {
+            let _: kw::left = input.parse()?;
+            Ok('<')
+        } else if lookahead.peek(kw::right) {
+            let _: kw::right = input.parse()?;
+            Ok('>')
+        } else if lookahead.peek(kw::center) {
+            let _: kw::center = input.parse()?;
+            Ok('^')
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl Display for FmtSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.fill, self.align) {
+            (Some(fill), Some(align)) => write!(f, "{}{}", fill, align)?,
+            (None, Some(align)) => write!(f, "{}", align)?,
+            (Some(fill), None) => write!(f, "{}", fill)?,
+            (None, None) => (),
+        }
+
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Output {
+    buf: String,
+    layout: Layout,
+    title: bool,
+    field_sep: String,
+    json: Option<Box<JsonOutput>>,
+    args: Vec<FormatArg>,
+    // Same message in the compact layout, chosen at runtime with the `runtime_fmt` feature
+    compact: Option<Box<Output>>,
+    diagnostic: Option<Box<Diagnostic>>,
+}
+
+enum Desc {
+    // Format string, which may reference fields
+    Lit(LitStr),
+    // Constant rendered via `Display` at runtime, e.g. `desc = errors::TIMEOUT_MSG`
+    Const(Path),
+}
+
+// Positional format argument, optionally wrapped into a helper type
+struct FormatArg {
+    value: FormatValue,
+    wrapper: Option<&'static str>,
+}
+
+enum FormatValue {
+    Field(Member),
+    Const(Path),
+    // Pairs of `context_map`, rendered with the separators of the fields
+    Context { line_sep: char, field_sep: String },
+}
+
+impl FormatArg {
+    fn wrap(&self, value: TokenStream) -> TokenStream {
+        match self.wrapper {
+            Some(wrapper) => {
+                let wrapper = format_ident!("{}", wrapper);
+                quote!(::justerror::__private::#wrapper(#value))
+            }
+            None => value,
+        }
+    }
+}
+
+enum Layout {
+    Multiline,
+    Compact,
+}
+
+impl Layout {
+    fn derive(root: &ErrorArgs, variant: &Option<ErrorArgs>) -> Self {
+        let compact = match variant {
+            Some(ErrorArgs {
+                compact: Some(compact),
+                ..
+            }) => *compact,
+            Some(_) | None => root.compact.unwrap_or(false),
+        };
+
+        if compact {
+            Layout::Compact
+        } else {
+            Layout::Multiline
+        }
+    }
+
+    fn line_sep(&self) -> char {
+        match self {
+            Layout::Multiline => '\n',
+            Layout::Compact => ' ',
+        }
+    }
+
+    fn field_sep(&self) -> &'static str {
+        match self {
+            Layout::Multiline => ": ",
+            Layout::Compact => "=",
+        }
+    }
+}
+
+enum FieldIdentStyle {
+    Prefixed,
+    Unprefixed,
+}
+
+impl Output {
+    fn new(root: &ErrorArgs, variant: &Option<ErrorArgs>) -> Self {
+        let mut output = Self::with_layout(root, variant, Layout::derive(root, variant));
+
+        // An explicit `compact` fixes the layout
+        let explicit = match variant {
+            Some(ErrorArgs {
+                compact: Some(_), ..
+            }) => true,
+            Some(_) | None => root.compact.is_some(),
+        };
+
+        if cfg!(feature = "miette") {
+            output.diagnostic = Some(Box::default());
+        }
+
+        if cfg!(feature = "runtime_fmt") && output.json.is_none() && !explicit {
+            output.compact = Some(Box::new(Self::with_layout(root, variant, Layout::Compact)));
+        }
+
+        output
+    }
+
+    fn with_layout(root: &ErrorArgs, variant: &Option<ErrorArgs>, layout: Layout) -> Self {
+        let field_sep = match (&root.sep, variant) {
+            (_, Some(ErrorArgs { sep: Some(sep), .. }))
+            | (Some(sep), Some(ErrorArgs { sep: None, .. }) | None) => sep.to_owned(),
+            (None, Some(ErrorArgs { sep: None, .. }) | None) => layout.field_sep().to_owned(),
+        };
+
+        let json = match Fmt::derive(root, variant, &None) {
+            Fmt::Json => Some(Box::default()),
+            _ => None,
+        };
+
+        let title = match variant {
+            Some(ErrorArgs {
+                title: Some(title), ..
+            }) => *title,
+            Some(_) | None => root.title.unwrap_or(true),
+        };
+
+        Self {
+            buf: String::new(),
+            layout,
+            title,
+            field_sep,
+            json,
+            args: Vec::new(),
+            compact: None,
+            diagnostic: None,
+        }
+    }
+
+    // Format string used as is, without the title and the fields
+    fn raw(format: String) -> Self {
+        Self {
+            buf: format,
+            layout: Layout::Multiline,
+            title: false,
+            field_sep: String::new(),
+            json: None,
+            args: Vec::new(),
+            compact: None,
+            diagnostic: None,
+        }
+    }
+
+    fn format_string(&self) -> String {
+        match &self.json {
+            Some(json) => json.format(),
+            None => self.buf.clone(),
+        }
+    }
+
+    fn push_code(&mut self, code: u32) {
+        if let Some(diagnostic) = &mut self.diagnostic {
+            diagnostic.push_code(code);
+        }
+
+        if let Some(compact) = &mut self.compact {
+            compact.push_code(code);
+        }
+
+        if let Some(json) = &mut self.json {
+            return json.push_code(code);
+        }
+
+        // The code is a part of the title line
+        if !self.title {
+            return;
+        }
+
+        let buf = &mut self.buf;
+
+        buf.push_str("[E");
+        buf.push_str(&code.to_string());
+        buf.push_str("] ");
+    }
+
+    fn push_title(
+        &mut self,
+        prefix: Option<&str>,
+        head: &Ident,
+        tail: Option<&Ident>,
+        separator: Option<&LitStr>,
+    ) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_title(prefix, head, tail, separator);
+        }
+
+        if let Some(json) = &mut self.json {
+            return json.push_title(head.to_string(), tail.map(Ident::to_string));
+        }
+
+        if !self.title {
+            return;
+        }
+
+        let buf = &mut self.buf;
+
+        // Unlike `desc`, the prefix is not a format string
+        if let Some(prefix) = prefix {
+            buf.push_str(&prefix.replace('{', "{{").replace('}', "}}"));
+            buf.push(' ');
+        }
+
+        buf.push_str(&head.to_string());
+
+        if let Some(tail) = tail {
+            match separator {
+                Some(separator) => {
+                    buf.push_str(&separator.value().replace('{', "{{").replace('}', "}}"))
+                }
+                None => buf.push_str("::"),
+            }
+            buf.push_str(&tail.to_string());
+        }
+    }
+
+    // The field marked with `#[reason]` follows the title, it is skipped by `push_fields`
+    fn push_reason(&mut self, fields: &Fields) -> Result<(), TokenStream> {
+        if let Some(compact) = &mut self.compact {
+            compact.push_reason(fields)?;
+        }
+
+        let mut reason = None;
+
+        for (idx, field) in fields.iter().enumerate() {
+            let attr = match field
+                .attrs
+                .iter()
+                .find(|attr| attr.path.is_ident(REASON_ATTR))
+            {
+                Some(attr) => attr,
+                None => continue,
+            };
+
+            if reason.is_some() {
+                return Err(SyntaxError::new_spanned(
+                    attr,
+                    "`#[reason]` is already defined, only one field can be the reason",
+                )
+                .into_compile_error());
+            }
+
+            if !self.title {
+                return Err(SyntaxError::new_spanned(
+                    attr,
+                    "`#[reason]` is rendered in the title, it can't be combined with `title = false`",
+                )
+                .into_compile_error());
+            }
+
+            reason = Some(match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(idx.into()),
+            });
+        }
+
+        let reason = match reason {
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+
+        if let Some(json) = &mut self.json {
+            json.push_reason();
+            self.args.push(FormatArg {
+                value: FormatValue::Field(reason),
+                wrapper: Some("JsonDisplay"),
+            });
+            return Ok(());
+        }
+
+        self.buf.push_str(": {");
+        self.buf.push_str(&match reason {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(idx) => idx.index.to_string(),
+        });
+        self.buf.push('}');
+
+        Ok(())
+    }
+
+    fn push_desc(
+        &mut self,
+        prefix: Option<&Ident>,
+        desc: &Desc,
+        type_name: &Ident,
+        variant_name: &Ident,
+    ) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_desc(prefix, desc, type_name, variant_name);
+        }
+
+        let desc = match desc {
+            Desc::Lit(desc) => {
+                let desc = expand_desc_placeholders(&desc.value(), type_name, variant_name);
+                if let Some(diagnostic) = &mut self.diagnostic {
+                    diagnostic.push_desc(&desc);
+                }
+                desc
+            }
+            Desc::Const(path) => {
+                let wrapper = self.json.as_ref().map(|_| "JsonEscaped");
+                self.args.push(FormatArg {
+                    value: FormatValue::Const(path.clone()),
+                    wrapper,
+                });
+                "{}".to_string()
+            }
+        };
+
+        if let Some(json) = &mut self.json {
+            return json.push_desc(&desc);
+        }
+
+        let buf = &mut self.buf;
+
+        push_line_sep(buf, self.layout.line_sep());
+
+        if let Some(prefix) = prefix {
+            buf.push_str(&prefix.to_string());
+            buf.push_str(": ");
+        }
+
+        buf.push_str(&desc);
+    }
+
+    fn push_category(&mut self, category: &str) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_category(category);
+        }
+
+        if let Some(json) = &mut self.json {
+            return json.push_category(category);
+        }
+
+        let buf = &mut self.buf;
+
+        push_line_sep(buf, self.layout.line_sep());
+        buf.push_str("category: ");
+        buf.push_str(&category.replace('{', "{{").replace('}', "}}"));
+    }
+
+    // Same format string as `desc`
+    fn push_help(&mut self, help: &LitStr, type_name: &Ident, variant_name: &Ident) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_help(help, type_name, variant_name);
+        }
+
+        let help = expand_desc_placeholders(&help.value(), type_name, variant_name);
+
+        if let Some(diagnostic) = &mut self.diagnostic {
+            diagnostic.push_help(&help);
+        }
+
+        if let Some(json) = &mut self.json {
+            return json.push_help(&help);
+        }
+
+        let buf = &mut self.buf;
+
+        push_line_sep(buf, self.layout.line_sep());
+        buf.push_str("hint: ");
+        buf.push_str(&help);
+    }
+
+    fn push_context(&mut self) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_context();
+        }
+
+        self.buf.push_str("{}");
+        self.args.push(FormatArg {
+            value: FormatValue::Context {
+                line_sep: self.layout.line_sep(),
+                field_sep: self.field_sep.clone(),
+            },
+            wrapper: None,
+        });
+    }
+
+    fn push_url(&mut self, url: &str, code: Option<u32>) {
+        if let Some(compact) = &mut self.compact {
+            compact.push_url(url, code);
+        }
+
+        // Braces of templated links are a part of the URL, rather than the format string
+        let url = match (url.ends_with('/'), code) {
+            (true, Some(code)) => format!("{}E{}", url, code),
+            _ => url.to_owned(),
+        }
+        .replace('{', "{{")
+        .replace('}', "}}");
+
+        if let Some(diagnostic) = &mut self.diagnostic {
+            diagnostic.push_url(&url);
+        }
+
+        if let Some(json) = &mut self.json {
+            return json.push_url(url);
+        }
+
+        let buf = &mut self.buf;
+
+        push_line_sep(buf, self.layout.line_sep());
+        buf.push_str("see: ");
+        buf.push_str(&url);
+    }
+
+    fn push_debug_title(&mut self, header: &Option<LitStr>) {
+        if let (Layout::Compact, _) | (_, Some(_)) = (&self.layout, &self.json) {
+            return;
+        }
+
+        let buf = &mut self.buf;
+
+        let header = match header {
+            Some(header) => header.value(),
+            None => DEFAULT_HEADER.to_string(),
+        };
+
+        if header.is_empty() {
+            return;
+        }
+
+        push_line_sep(buf, '\n');
+        buf.push_str(&header.replace('{', "{{").replace('}', "}}"));
+    }
+
+    fn push_fields(
+        &mut self,
+        fields: &mut Fields,
+        error_args: &ErrorArgs,
+        variant_error_args: &Option<ErrorArgs>,
+    ) -> Result<(), TokenStream> {
+        // Field attributes are consumed below, so the compact layout gets a copy of them
+        if let Some(compact) = &mut self.compact {
+            compact.push_fields(&mut fields.clone(), error_args, variant_error_args)?;
+        }
+
+        let output = self;
+
+        check_from_field(fields)?;
+        check_backtrace_field(fields)?;
+
+        let skipped_fields = take_skipped_fields(fields);
+
+        if skipped_fields.iter().all(|skipped| *skipped) {
+            return Ok(());
+        }
+
+        match fields {
+            Fields::Named(fields) => {
+                output.push_debug_title(&error_args.header);
+
+                for (field, skipped) in fields.named.iter_mut().zip(skipped_fields) {
+                    if skipped {
+                        continue;
+                    }
+
+                    if let Some(field_ident) = field.ident.clone() {
+                        output.push_field(
+                            field,
+                            Member::Named(field_ident),
+                            &FieldIdentStyle::Prefixed,
+                            error_args,
+                            variant_error_args,
+                        )?;
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                output.push_debug_title(&error_args.header);
+
+                let displayed_fields = skipped_fields.iter().filter(|skipped| !**skipped).count();
+
+                let ident_style = if displayed_fields > 1 {
+                    FieldIdentStyle::Prefixed
+                } else {
+                    FieldIdentStyle::Unprefixed
+                };
+
+                // Indices are taken before skipping so `{idx}` still points to the right field
+                for ((idx, field), skipped) in
+                    fields.unnamed.iter_mut().enumerate().zip(skipped_fields)
+                {
+                    if skipped {
+                        continue;
+                    }
+
+                    output.push_field(
+                        field,
+                        Member::Unnamed(idx.into()),
+                        &ident_style,
+                        error_args,
+                        variant_error_args,
+                    )?;
+                }
+            }
+            Fields::Unit => (),
+        }
+
+        Ok(())
+    }
+
+    fn push_fields_format(
+        &mut self,
+        format: &LitStr,
+        fields: &mut Fields,
+    ) -> Result<(), TokenStream> {
+        if let Some(compact) = &mut self.compact {
+            compact.push_fields_format(format, &mut fields.clone())?;
+        }
+
+        check_from_field(fields)?;
+        check_backtrace_field(fields)?;
+        take_field_attrs(fields);
+
+        push_line_sep(&mut self.buf, self.layout.line_sep());
+        self.buf.push_str(&number_implicit_args(&format.value()));
+
+        Ok(())
+    }
+
+    // The single field of `aggregate` variants holds the errors, rendered as a numbered list
+    fn push_aggregate(
+        &mut self,
+        aggregate: &kw::aggregate,
+        fields: &mut Fields,
+        error_args: &ErrorArgs,
+    ) -> Result<(), TokenStream> {
+        if let Some(compact) = &mut self.compact {
+            compact.push_aggregate(aggregate, &mut fields.clone(), error_args)?;
+        }
+
+        if self.json.is_some() {
+            return Err(SyntaxError::new(
+                aggregate.span,
+                "`aggregate` can't be combined with `fmt = json`",
+            )
+            .into_compile_error());
+        }
+
+        check_from_field(fields)?;
+        check_backtrace_field(fields)?;
+
+        let skipped_fields = take_skipped_fields(fields);
+        take_field_attrs(fields);
+
+        let mut members = fields
+            .iter()
+            .enumerate()
+            .zip(skipped_fields)
+            .filter(|(_, skipped)| !*skipped)
+            .map(|((idx, field), _)| match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(idx.into()),
+            });
+
+        let member = match (members.next(), members.next()) {
+            (Some(member), None) => member,
+            _ => {
+                return Err(SyntaxError::new(
+                    aggregate.span,
+                    "`aggregate` requires a single field with the errors",
+                )
+                .into_compile_error())
+            }
+        };
+
+        self.push_debug_title(&error_args.header);
+        push_line_sep(&mut self.buf, self.layout.line_sep());
+        self.buf.push_str("{}");
+
+        let wrapper = match self.layout {
+            Layout::Multiline => "Numbered",
+            Layout::Compact => "NumberedInline",
+        };
+        self.args.push(FormatArg {
+            value: FormatValue::Field(member),
+            wrapper: Some(wrapper),
+        });
+
+        Ok(())
+    }
+
+    fn push_field(
+        &mut self,
+        field: &mut Field,
+        member: Member,
+        ident_style: &FieldIdentStyle,
+        error_args: &ErrorArgs,
+        variant_error_args: &Option<ErrorArgs>,
+    ) -> Result<(), TokenStream> {
+        let mut field_fmt_attr = None;
+
+        for (idx, attr) in field.attrs.iter().enumerate() {
+            if attr.path.is_ident(FMT_ATTR) {
+                field_fmt_attr = match attr.parse_args_with(Fmt::parse_field) {
+                    Ok(fmt) => Some((idx, fmt)),
+                    Err(err) => return Err(err.into_compile_error()),
+                };
+            }
+        }
+
+        let (field_fmt_attr_idx, field_fmt) = match field_fmt_attr {
+            Some((idx, fmt)) => (Some(idx), Some(fmt)),
+            None => (None, None),
+        };
+
+        if let Some(idx) = field_fmt_attr_idx {
+            field.attrs.remove(idx);
+        }
+
+        // Alternate `Display` of `anyhow::Error` renders the whole chain of causes
+        let field_fmt = match (field_fmt, &error_args.anyhow_compat) {
+            (None, Some(_)) if is_anyhow_error(&field.ty) => Some(Fmt::Custom("#".to_string())),
+            (field_fmt, _) => field_fmt,
+        };
+
+        // Applied to the field itself, so `fmt` of the variant doesn't override it
+        let field_fmt = match (field_fmt, &error_args.all_fields_debug) {
+            (None, Some(_)) => Some(Fmt::Debug),
+            (field_fmt, _) => field_fmt,
+        };
+
+        let sensitive = field
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident(SENSITIVE_ATTR));
+
+        field
+            .attrs
+            .retain(|attr| !attr.path.is_ident(SENSITIVE_ATTR));
+
+        // Labels are a part of the format string
+        let label = take_label(field)?.map(|label| label.replace('{', "{{").replace('}', "}}"));
+
+        let ident = match &member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(idx) => idx.index.to_string(),
+        };
+
+        if let Some(Fmt::Json) = field_fmt {
+            return Err(SyntaxError::new_spanned(
+                field,
+                "`json` is only supported at the root or variant level",
+            )
+            .into_compile_error());
+        }
+
+        if let Some(json) = &mut self.json {
+            let value = JsonValue::derive(field, matches!(field_fmt, Some(Fmt::Debug)), sensitive);
+
+            json.push_field(label.as_ref().unwrap_or(&ident), &value);
+
+            if let JsonValue::Raw | JsonValue::Display | JsonValue::Debug = value {
+                self.args.push(FormatArg {
+                    value: FormatValue::Field(member),
+                    wrapper: value.wrapper(),
+                });
+            }
+
+            return Ok(());
+        }
+
+        let fmt = Fmt::derive(error_args, variant_error_args, &field_fmt);
+
+        if let Fmt::Truncate(_) = fmt {
+            if is_number_field(field) {
+                return Err(SyntaxError::new_spanned(
+                    &field.ty,
+                    "`truncate` is only supported for strings, set `#[fmt(display)]` on number fields",
+                )
+                .into_compile_error());
+            }
+        }
+
+        // Nested errors span multiple lines, so the lines after the first are indented
+        let nested = match (&error_args.nest, &self.layout, &fmt) {
+            (Some(_), Layout::Multiline, Fmt::Display) => !sensitive,
+            _ => false,
+        };
+
+        let buf = &mut self.buf;
+
+        let fmt = match (&self.layout, fmt) {
+            // Pretty debug output spans multiple lines
+            (Layout::Compact, Fmt::Debug) => ":?".to_string(),
+            (_, fmt) => fmt.to_string(),
+        };
+
+        push_line_sep(buf, self.layout.line_sep());
+
+        // A label is displayed even for a single unnamed field
+        match (&label, ident_style) {
+            (Some(label), _) => {
+                buf.push_str(label);
+                buf.push_str(&self.field_sep);
+            }
+            (None, FieldIdentStyle::Prefixed) => {
+                buf.push_str(&ident);
+                buf.push_str(&self.field_sep);
+            }
+            (None, FieldIdentStyle::Unprefixed) => (),
+        }
+
+        // The value is not referenced at all, so its type doesn't need `Display`
+        if sensitive {
+            buf.push_str(REDACTED);
+        } else if nested {
+            buf.push_str("{}");
+            self.args.push(FormatArg {
+                value: FormatValue::Field(member),
+                wrapper: Some("Indent"),
+            });
+        } else {
+            buf.push('{');
+            buf.push_str(&ident);
+            buf.push_str(&fmt);
+            buf.push('}');
+        }
+
+        Ok(())
+    }
+}
+
+// Field attributes of justerror are not known to `thiserror` and have to go
+fn take_field_attrs(fields: &mut Fields) {
+    for field in fields.iter_mut() {
+        field.attrs.retain(|attr| {
+            !attr.path.is_ident(FMT_ATTR)
+                && !attr.path.is_ident(SKIP_ATTR)
+                && !attr.path.is_ident(SENSITIVE_ATTR)
+                && !is_label_attr(attr)
+                && !attr.path.is_ident(REASON_ATTR)
+        });
+    }
+}
+
+// Labels of `miette` spans are kept for its derive
+fn is_label_attr(attr: &Attribute) -> bool {
+    attr.path.is_ident(LABEL_ATTR) && !miette::is_span_label(attr)
+}
+
+// `#[label = "..."]` replaces the field name in the output
+fn take_label(field: &mut Field) -> Result<Option<String>, TokenStream> {
+    let mut label = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(LABEL_ATTR))
+    {
+        if label.is_some() {
+            return Err(
+                SyntaxError::new_spanned(attr, "`#[label]` is already defined")
+                    .into_compile_error(),
+            );
+        }
+
+        label = match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(str), ..
+            })) => Some(str.value()),
+            _ => {
+                return Err(SyntaxError::new_spanned(
+                    attr,
+                    "`label` must be a string, e.g. `#[label = \"request timeout (ms)\"]`",
+                )
+                .into_compile_error())
+            }
+        };
+    }
+
+    field.attrs.retain(|attr| !attr.path.is_ident(LABEL_ATTR));
+
+    Ok(label)
+}
+
+// Fields marked with `#[skip]` and backtraces (provided via `Error::provide`) are not displayed,
+// fields marked with `#[reason]` are displayed in the title
+fn take_skipped_fields(fields: &mut Fields) -> Vec<bool> {
+    fields
+        .iter_mut()
+        .map(|field| {
+            if miette::is_source_span(field) {
+                miette::convert_span_label(field);
+            }
+
+            let skipped = is_backtrace_field(field)
+                || is_phantom_data_field(field)
+                || miette::is_source_span(field)
+                || field.attrs.iter().any(|attr| {
+                    attr.path.is_ident(SKIP_ATTR)
+                        || attr.path.is_ident(REASON_ATTR)
+                        || is_fmt_skip_attr(attr)
+                });
+
+            if skipped {
+                field.attrs.retain(|attr| {
+                    !attr.path.is_ident(SKIP_ATTR)
+                        && !attr.path.is_ident(FMT_ATTR)
+                        && !attr.path.is_ident(SENSITIVE_ATTR)
+                        && !is_label_attr(attr)
+                        && !attr.path.is_ident(REASON_ATTR)
+                });
+            }
+
+            skipped
+        })
+        .collect()
+}
+
+// `#[fmt(skip)]` is the same as `#[skip]`
+fn is_fmt_skip_attr(attr: &Attribute) -> bool {
+    attr.path.is_ident(FMT_ATTR) && matches!(attr.parse_args_with(Fmt::parse_field), Ok(Fmt::Skip))
+}
+
+fn extract_doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(DOC_ATTR))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(str), ..
+            })) => Some(str.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let first = lines.iter().position(|line| !line.is_empty())?;
+    let last = lines.iter().rposition(|line| !line.is_empty())?;
+
+    // Doc comments are plain text, so braces must not end up as format arguments
+    let doc = lines[first..=last]
+        .join("\n")
+        .replace('{', "{{")
+        .replace('}', "}}");
+
+    Some(doc)
+}
+
+// Without the title, the message starts with whatever comes next and must not lead with a break
+fn push_line_sep(buf: &mut String, line_sep: char) {
+    if !buf.is_empty() {
+        buf.push(line_sep);
+    }
+}
+
+// `#[fmt("...")]` of a variant replaces its fields with a format string referencing them
+fn take_fields_format(attrs: &mut Vec<Attribute>) -> Result<Option<LitStr>, TokenStream> {
+    let mut format = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(FMT_ATTR)) {
+        if format.is_some() {
+            return Err(
+                SyntaxError::new_spanned(attr, "`#[fmt]` is already defined").into_compile_error(),
+            );
+        }
+
+        format = match attr.parse_args::<LitStr>() {
+            Ok(str) => Some(str),
+            Err(_) => {
+                return Err(SyntaxError::new_spanned(
+                    attr,
+                    "`#[fmt]` of a variant must be a format string of its fields, e.g. `#[fmt(\"{a}/{b}\")]`",
+                )
+                .into_compile_error())
+            }
+        };
+    }
+
+    attrs.retain(|attr| !attr.path.is_ident(FMT_ATTR));
+
+    Ok(format)
+}
+
+// Implicit positions `{}` refer to the fields in order, e.g. `{}/{}` is `{0}/{1}`
+fn number_implicit_args(format: &str) -> String {
+    let mut result = String::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+    let mut position = 0;
+
+    while let Some(char) = chars.next() {
+        result.push(char);
+
+        if char == '{' {
+            match chars.peek() {
+                Some('{') => result.extend(chars.next()),
+                Some('}') | Some(':') => {
+                    result.push_str(&position.to_string());
+                    position += 1;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    result
+}
+
+// `{type}` and `{variant}` are replaced with names at expansion time, `{{type}}` stays escaped
+fn expand_desc_placeholders(desc: &str, type_name: &Ident, variant_name: &Ident) -> String {
+    let mut result = String::with_capacity(desc.len());
+    let mut rest = desc;
+
+    while let Some(idx) = rest.find('{') {
+        result.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        if rest.starts_with("{{") {
+            result.push_str("{{");
+            rest = &rest[2..];
+        } else if rest.starts_with("{type}") {
+            result.push_str(&type_name.to_string());
+            rest = &rest["{type}".len()..];
+        } else if rest.starts_with("{variant}") {
+            result.push_str(&variant_name.to_string());
+            rest = &rest["{variant}".len()..];
+        } else {
+            result.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn is_backtrace_field(field: &Field) -> bool {
+    if field
+        .attrs
+        .iter()
+        .any(|attr| attr.path.is_ident(BACKTRACE_ATTR))
+    {
+        return true;
+    }
+
+    match &field.ty {
+        Type::Path(ty) => match ty.path.segments.last() {
+            Some(segment) => segment.ident == "Backtrace",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// Types are not resolved, so only the usual spelling is recognized
+fn is_anyhow_error(ty: &Type) -> bool {
+    let ty = ty.to_token_stream().to_string();
+    ty == "anyhow :: Error" || ty == ":: anyhow :: Error"
+}
+
+// Tokens of types are spaced out, e.g. `std :: io :: Error`
+fn type_name(ty: &Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+}
+
+// Markers carry no data, and `PhantomData` doesn't implement `Display`
+fn is_phantom_data_field(field: &Field) -> bool {
+    match &field.ty {
+        Type::Path(ty) => match ty.path.segments.last() {
+            Some(segment) => segment.ident == "PhantomData",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// Paths don't implement `Display`, so they are displayed via `display()` like `thiserror` does
+fn is_path_field(field: &Field) -> bool {
+    let mut ty = &field.ty;
+    while let Type::Reference(reference) = ty {
+        ty = &reference.elem;
+    }
+
+    match ty {
+        Type::Path(ty) => match ty.path.segments.last() {
+            Some(segment) => segment.ident == "Path" || segment.ident == "PathBuf",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// `{:.N}` truncates strings, but sets the decimal places of floats and is ignored by integers
+fn is_number_field(field: &Field) -> bool {
+    const NUMBER_TYPES: &[&str] = &[
+        "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+        "u128", "usize",
+    ];
+
+    let mut ty = &field.ty;
+    while let Type::Reference(reference) = ty {
+        ty = &reference.elem;
+    }
+
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => ty
+            .path
+            .get_ident()
+            .is_some_and(|ident| NUMBER_TYPES.iter().any(|number| ident == number)),
+        _ => false,
+    }
+}
+
+// Named and numbered placeholders of a format string along with their spec, if any
+fn field_placeholders(format: &str) -> Vec<(String, Option<String>)> {
+    let mut placeholders = Vec::new();
+    let mut rest = format;
+
+    while let Some(idx) = rest.find('{') {
+        rest = &rest[idx..];
+
+        if rest.starts_with("{{") {
+            rest = &rest[2..];
+            continue;
+        }
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let placeholder = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let (name, spec) = match placeholder.find(':') {
+            Some(colon) => (&placeholder[..colon], Some(&placeholder[colon + 1..])),
+            None => (placeholder, None),
+        };
+
+        if !name.is_empty() {
+            placeholders.push((name.to_owned(), spec.map(ToOwned::to_owned)));
+        }
+    }
+
+    placeholders
+}
+
+// Numbered fields are `{0}` in the messages and `{_0}` once bound in standalone impls
+fn placeholder_field<'a>(fields: &'a Fields, name: &str) -> Option<(usize, &'a Field)> {
+    match fields {
+        Fields::Named(_) => fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.ident.as_ref().is_some_and(|ident| ident == name)),
+        Fields::Unnamed(_) => {
+            let idx = name.trim_start_matches('_').parse::<usize>().ok()?;
+            fields.iter().nth(idx).map(|field| (idx, field))
+        }
+        Fields::Unit => None,
+    }
+}
+
+// `thiserror` wraps `{field}` into its `AsDisplay` helper referred to by a bare `thiserror::` path,
+// which only resolves if the user depends on `thiserror`, so such fields are passed as named arguments
+fn display_args(message: &Message, fields: &Fields) -> Vec<TokenStream> {
+    let output = match message {
+        Message::Format(output) => output,
+        Message::Transparent => return Vec::new(),
+    };
+
+    let mut names = Vec::new();
+    let mut args = Vec::new();
+
+    for (name, spec) in field_placeholders(&output.format_string()) {
+        if spec.is_some() || names.contains(&name) {
+            continue;
+        }
+
+        let (idx, field) = match placeholder_field(fields, &name) {
+            Some(field) => field,
+            None => continue,
+        };
+        let (var, member) = match &field.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => (format_ident!("_{}", idx), Member::Unnamed(idx.into())),
+        };
+
+        if is_path_field(field) {
+            args.push(quote!(#var = .#member.display()));
+        } else {
+            args.push(quote!(#var = .#member));
+        }
+        names.push(name);
+    }
+
+    args
+}
+
+fn check_desc_fields(desc: &Desc, fields: &Fields) -> Result<(), TokenStream> {
+    match desc {
+        Desc::Lit(desc) => check_field_refs(desc, "desc", fields),
+        Desc::Const(_) => Ok(()),
+    }
+}
+
+// Fields referenced in format strings are reported here rather than as unresolved format arguments
+fn check_field_refs(format: &LitStr, arg: &str, fields: &Fields) -> Result<(), TokenStream> {
+    let value = number_implicit_args(&format.value());
+    let mut rest = value.as_str();
+
+    while let Some(idx) = rest.find(['{', '}']) {
+        rest = &rest[idx..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            rest = &rest[2..];
+            continue;
+        }
+        if rest.starts_with('}') {
+            rest = &rest[1..];
+            continue;
+        }
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => return Ok(()),
+        };
+        let name = rest[1..end].split(':').next().unwrap_or_default().trim();
+        rest = &rest[end + 1..];
+
+        if name.is_empty() || name == "type" || name == "variant" {
+            continue;
+        }
+
+        let exists = match fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .any(|field| field.ident.as_ref().is_some_and(|ident| ident == name)),
+            Fields::Unnamed(fields) => match name.parse::<usize>() {
+                Ok(idx) => idx < fields.unnamed.len(),
+                Err(_) => false,
+            },
+            Fields::Unit => false,
+        };
+
+        if !exists {
+            return Err(SyntaxError::new(
+                format.span(),
+                format!("`{}` references unknown field `{}`", arg, name),
+            )
+            .into_compile_error());
+        }
+    }
+
+    Ok(())
+}
+
+fn check_transparent_fields(fields: &Fields, ident: &Ident) -> Result<(), TokenStream> {
+    if fields.len() == 1 {
+        Ok(())
+    } else {
+        Err(
+            SyntaxError::new_spanned(ident, "`transparent` requires exactly one field")
+                .into_compile_error(),
+        )
+    }
+}
+
+fn check_backtrace_field(fields: &Fields) -> Result<(), TokenStream> {
+    if cfg!(feature = "backtrace") {
+        return Ok(());
+    }
+
+    let backtrace_attr = fields
+        .iter()
+        .flat_map(|field| field.attrs.iter())
+        .find(|attr| attr.path.is_ident(BACKTRACE_ATTR));
+
+    match backtrace_attr {
+        Some(attr) => Err(SyntaxError::new_spanned(
+            attr,
+            "`#[backtrace]` requires the `backtrace` feature of justerror (nightly only)",
+        )
+        .into_compile_error()),
+        None => Ok(()),
+    }
+}
+
+// thiserror only derives `From` when the `#[from]` field is the only one (besides a backtrace)
+// Each `#[from]` generates a `From` impl, so two of them can't convert the same type.
+// Variants behind `#[cfg(...)]` may be mutually exclusive, so they are not checked.
+fn check_from_types(variants: &Punctuated<Variant, Token![,]>) -> Result<(), TokenStream> {
+    let mut from_types: HashMap<String, &Ident> = HashMap::new();
+
+    for variant in variants {
+        if !cfg_attrs(&variant.attrs).is_empty() {
+            continue;
+        }
+
+        let from_field = variant
+            .fields
+            .iter()
+            .find(|field| field.attrs.iter().any(|attr| attr.path.is_ident(FROM_ATTR)));
+
+        if let Some(field) = from_field {
+            let ty = field.ty.to_token_stream().to_string();
+
+            if let Some(first) = from_types.get(&ty) {
+                return Err(SyntaxError::new_spanned(
+                    &field.ty,
+                    format!(
+                        "`#[from]` of this type is already defined on `{}`, only one variant can convert from it",
+                        first
+                    ),
+                )
+                .into_compile_error());
+            }
+
+            from_types.insert(ty, &variant.ident);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_from_field(fields: &Fields) -> Result<(), TokenStream> {
+    let from_attr = fields
+        .iter()
+        .flat_map(|field| field.attrs.iter())
+        .find(|attr| attr.path.is_ident(FROM_ATTR));
+
+    if let Some(from_attr) = from_attr {
+        let has_other_fields = fields.iter().any(|field| {
+            !field.attrs.iter().any(|attr| attr.path.is_ident(FROM_ATTR))
+                && !is_backtrace_field(field)
+        });
+
+        if has_other_fields {
+            return Err(SyntaxError::new_spanned(
+                from_attr,
+                "`#[from]` is only supported on a variant or struct with a single field (besides a backtrace)",
+            )
+            .into_compile_error());
+        }
+    }
+
+    Ok(())
+}
+
+impl ToTokens for Output {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let format = self.format_string();
+        // `thiserror` resolves `.field` in arguments to the field of the variant
+        let args = self.args.iter().map(|arg| match &arg.value {
+            FormatValue::Field(member) => arg.wrap(quote!(.#member)),
+            FormatValue::Const(path) => arg.wrap(quote!(#path)),
+            FormatValue::Context {
+                line_sep,
+                field_sep,
+            } => {
+                let context = format_ident!("{}", CONTEXT_FIELD);
+                quote!(::justerror::__private::Context(&.#context, #line_sep, #field_sep))
+            }
+        });
+
+        quote!(#format #(, #args)*).to_tokens(tokens)
+    }
+}
+
+impl ToTokens for Message {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Message::Transparent => quote!(transparent).to_tokens(tokens),
+            Message::Format(output) => output.to_tokens(tokens),
+        }
+    }
+}
+
+fn error_code_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generated methods are part of the API, so private types shouldn't warn about unused ones.
+    // Deprecated types and variants are not deprecated for the generated code itself.
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the error code, if any.
+            #vis fn error_code(&self) -> ::core::option::Option<u32> {
+                #body
+            }
+        }
+    }
+}
+
+// Frameworks define their own traits for error codes, e.g. `trait ErrorCode { fn code(&self) -> u32; }`
+fn error_code_trait_impl(
+    path: &Path,
+    ident: &Ident,
+    generics: &Generics,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #path for #ident #ty_generics #where_clause {
+            fn code(&self) -> u32 {
+                #body
+            }
+        }
+    }
+}
+
+fn exit_code_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the process exit code for this error.
+            #vis fn exit_code(&self) -> i32 {
+                #body
+            }
+        }
+    }
+}
+
+// `ExitCode` only holds a `u8`, and `0` would report the error as a success
+fn check_termination_exit_code(exit_code: i32, span: Span) -> syn::Result<()> {
+    if !(1..=255).contains(&exit_code) {
+        return Err(SyntaxError::new(
+            span,
+            "`exit_code` must be in range 1..=255 with `termination`",
+        ));
+    }
+
+    Ok(())
+}
+
+// `main` returning the error prints its message instead of `Debug` output
+fn termination_impl(ident: &Ident, generics: &Generics, exit_code: bool) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let mut generics = generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::core::fmt::Display));
+    let where_clause = &generics.where_clause;
+
+    let exit_code = if exit_code {
+        quote!(::std::process::ExitCode::from(self.exit_code() as u8))
+    } else {
+        quote!(::std::process::ExitCode::FAILURE)
+    };
+
+    quote! {
+        #[allow(deprecated)]
+        impl #impl_generics ::std::process::Termination for #ident #ty_generics #where_clause {
+            fn report(self) -> ::std::process::ExitCode {
+                ::std::eprintln!("{}", self);
+                #exit_code
+            }
+        }
+    }
+}
+
+fn send_sync_assertion(error: &DeriveInput) -> TokenStream {
+    fields_assertion(error, quote!(::core::marker::Send + ::core::marker::Sync))
+}
+
+// Asserts each field separately, so errors point to the fields that don't satisfy `bound`
+fn fields_assertion(error: &DeriveInput, bound: TokenStream) -> TokenStream {
+    let fields: Vec<&Field> = match &error.data {
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    let assertions = fields.iter().map(|field| {
+        let ty = &field.ty;
+        quote_spanned!(ty.span()=> __assert_bound::<#ty>();)
+    });
+
+    // Type parameters are assumed to satisfy `bound`, so only the fields themselves are checked
+    let mut generics = error.generics.clone();
+    let params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    for param in params {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#param: #bound));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        const _: () = {
+            fn __assert_bound<T: ?::core::marker::Sized + #bound>() {}
+
+            #[allow(dead_code)]
+            fn __assert_fields #impl_generics () #where_clause {
+                #(#assertions)*
+            }
+        };
+    }
+}
+
+// Asserts that the type itself is `Hash`, fields which are not are reported by `#[derive(Hash)]`
+fn hash_assertion(hash: &kw::hash, error: &DeriveInput) -> TokenStream {
+    let ident = &error.ident;
+
+    let mut generics = error.generics.clone();
+    let params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    for param in params {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#param: ::core::hash::Hash));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let assertion = quote_spanned!(hash.span=> __assert_hash::<#ident #ty_generics>(););
+
+    quote! {
+        const _: () = {
+            fn __assert_hash<T: ?::core::marker::Sized + ::core::hash::Hash>() {}
+
+            #[allow(dead_code)]
+            fn __assert_type #impl_generics () #where_clause {
+                #assertion
+            }
+        };
+    }
+}
+
+// Fields are compared with `==` if all of them are `PartialEq`, otherwise the `Debug` outputs are
+fn equality_impl(error: &DeriveInput) -> TokenStream {
+    let arm = |path: TokenStream, cfgs: Vec<&Attribute>, fields: &Fields| {
+        let members: Vec<Member> = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(idx.into()),
+            })
+            .collect();
+        let lhs: Vec<Ident> = (0..members.len())
+            .map(|idx| format_ident!("__self_{}", idx))
+            .collect();
+        let rhs: Vec<Ident> = (0..members.len())
+            .map(|idx| format_ident!("__other_{}", idx))
+            .collect();
+
+        quote! {
+            #(#cfgs)*
+            (#path { #(#members: #lhs,)* }, #path { #(#members: #rhs,)* }) => {
+                let fields: &[::core::option::Option<bool>] = &[
+                    #((&::justerror::__private::EqProbe(#lhs, #rhs)).justerror_eq(),)*
+                ];
+                if fields.iter().all(::core::option::Option::is_some) {
+                    fields.iter().all(|eq| *eq == ::core::option::Option::Some(true))
+                } else {
+                    ::std::format!("{:?}", self) == ::std::format!("{:?}", other)
+                }
+            }
+        }
+    };
+
+    let body = match &error.data {
+        Data::Enum(data) if data.variants.is_empty() => quote!(match *self {}),
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                arm(
+                    quote!(Self::#ident),
+                    cfg_attrs(&variant.attrs),
+                    &variant.fields,
+                )
+            });
+
+            quote! {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #(#arms)*
+                    _ => false,
+                }
+            }
+        }
+        Data::Struct(data) => {
+            let arm = arm(quote!(Self), Vec::new(), &data.fields);
+            quote!(match (self, other) { #arm })
+        }
+        Data::Union(_) => return TokenStream::new(),
+    };
+
+    let ident = &error.ident;
+    let (impl_generics, ty_generics, _) = error.generics.split_for_impl();
+
+    let mut generics = error.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::core::fmt::Debug));
+    let where_clause = &generics.where_clause;
+
+    quote! {
+        #[allow(deprecated)]
+        impl #impl_generics ::core::cmp::PartialEq for #ident #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                #[allow(unused_imports)]
+                use ::justerror::__private::{EqFallback as _, EqViaEq as _};
+                #body
+            }
+        }
+
+        #[allow(deprecated)]
+        impl #impl_generics ::core::cmp::Eq for #ident #ty_generics #where_clause {}
+    }
+}
+
+fn sources_impl(ident: &Ident, vis: &Visibility, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let error_trait = standalone::error_trait();
+
+    // Only `'static` errors can be returned as `dyn Error + 'static`
+    let mut generics = generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: #error_trait + 'static));
+    let where_clause = &generics.where_clause;
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns an iterator over the error and its chain of sources.
+            #vis fn sources(&self) -> impl ::core::iter::Iterator<Item = &(dyn #error_trait + 'static)> {
+                ::justerror::__private::Sources(::core::option::Option::Some(self))
+            }
+        }
+    }
+}
+
+fn debug_impl(ident: &Ident, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let mut generics = generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::core::fmt::Display));
+    let where_clause = &generics.where_clause;
+
+    quote! {
+        #[allow(deprecated)]
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(self, f)
+            }
+        }
+    }
+}
+
+fn category_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the category of the error, or `""` if it has none.
+            #vis fn category(&self) -> &'static str {
+                #body
+            }
+        }
+    }
+}
+
+fn result_alias_item(alias: &Ident, error: &DeriveInput) -> TokenStream {
+    let vis = &error.vis;
+    let ident = &error.ident;
+    let (_, ty_generics, _) = error.generics.split_for_impl();
+
+    let mut ok = format_ident!("T");
+    while error.generics.type_params().any(|param| param.ident == ok) {
+        ok = format_ident!("{}_", ok);
+    }
+
+    // Bounds are not enforced on type aliases, so only the parameters are declared
+    let lifetimes = error.generics.lifetimes().map(|param| &param.lifetime);
+    let types = error.generics.type_params().map(|param| &param.ident);
+    let consts = error.generics.const_params().map(|param| {
+        let ident = &param.ident;
+        let ty = &param.ty;
+        quote!(const #ident: #ty)
+    });
+    let doc = format!("`Result` type with [`{}`] as the error type.", ident);
+
+    quote! {
+        #[doc = #doc]
+        #[allow(dead_code, deprecated)]
+        #vis type #alias<#(#lifetimes,)* #ok, #(#types,)* #(#consts),*> =
+            ::core::result::Result<#ok, #ident #ty_generics>;
+    }
+}
+
+fn variant_name_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the name of the variant, or of the struct.
+            #vis fn variant_name(&self) -> &'static str {
+                #body
+            }
+        }
+    }
+}
+
+// Arguments are named after the fields, `_0`, `_1`, etc. for tuple structs
+fn field_args(fields: &Fields) -> Vec<(Member, Ident, &Type)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.clone(), &field.ty),
+            None => (
+                Member::Unnamed(idx.into()),
+                format_ident!("_{}", idx),
+                &field.ty,
+            ),
+        })
+        .collect()
+}
+
+fn constructor_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    fields: &Fields,
+    error_args: &ErrorArgs,
+    sensitive: &[bool],
+    defaults: &[Option<TokenStream>],
+) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let where_clause = on_create_where_clause(generics, error_args, fields.iter());
+
+    let args = field_args(fields);
+    let (params, values) = constructor_args(&args, defaults);
+    let context = context_init(error_args.context_map.is_some());
+
+    let body = match on_create(error_args, ident, None, fields, sensitive) {
+        Some(on_create) => quote! {
+            let error = Self { #(#values,)* #context };
+            #on_create
+            error
+        },
+        None => quote!(Self { #(#values,)* #context }),
+    };
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Creates the error from its fields.
+            #[allow(clippy::too_many_arguments, clippy::new_without_default)]
+            #vis fn new(#(#params),*) -> Self {
+                #body
+            }
+        }
+    }
+}
+
+// Constructors of variants exist to run `on_create` with the created error
+fn variant_constructors_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    variants: &Punctuated<Variant, Token![,]>,
+    error_args: &ErrorArgs,
+    sensitive: &[Vec<bool>],
+    defaults: &[Vec<Option<TokenStream>>],
+) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let where_clause = on_create_where_clause(
+        generics,
+        error_args,
+        variants.iter().flat_map(|variant| variant.fields.iter()),
+    );
+
+    let constructors = variants.iter().enumerate().map(|(idx, variant)| {
+        let variant_ident = &variant.ident;
+        let constructor = format_ident!("new_{}", to_snake_case(&variant_ident.to_string()));
+        let doc = format!("Creates [`{}::{}`] from its fields.", ident, variant_ident);
+
+        let cfgs = cfg_attrs(&variant.attrs);
+
+        let args = field_args(&variant.fields);
+        let defaults = defaults.get(idx).map_or(&[][..], Vec::as_slice);
+        let (params, values) = constructor_args(&args, defaults);
+
+        let sensitive = sensitive.get(idx).map_or(&[][..], Vec::as_slice);
+        let on_create = on_create(
+            error_args,
+            ident,
+            Some(variant_ident),
+            &variant.fields,
+            sensitive,
+        );
+
+        quote! {
+            #[doc = #doc]
+            #(#cfgs)*
+            #[allow(clippy::too_many_arguments)]
+            #vis fn #constructor(#(#params),*) -> Self {
+                let error = Self::#variant_ident { #(#values,)* };
+                #on_create
+                error
+            }
+        }
+    });
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#constructors)*
+        }
+    }
+}
+
+// Fields with `#[default = ...]` are not parameters of constructors
+fn constructor_args(
+    args: &[(Member, Ident, &Type)],
+    defaults: &[Option<TokenStream>],
+) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut params = Vec::new();
+    let mut values = Vec::new();
+
+    for (idx, (member, arg, ty)) in args.iter().enumerate() {
+        match field_default(defaults, idx) {
+            Some(default) => values.push(quote!(#member: #default)),
+            None => {
+                params.push(quote!(#arg: #ty));
+                values.push(quote!(#member: #arg));
+            }
+        }
+    }
+
+    (params, values)
+}
+
+fn field_default(defaults: &[Option<TokenStream>], idx: usize) -> Option<&TokenStream> {
+    match defaults.get(idx) {
+        Some(default) => default.as_ref(),
+        None => None,
+    }
+}
+
+// `#[default = <expr>]` is taken off the fields before anything else sees them
+fn take_defaults(
+    data: &mut Data,
+    used: bool,
+) -> Result<Vec<Vec<Option<TokenStream>>>, TokenStream> {
+    let take = |fields: &mut Fields| -> Result<Vec<Option<TokenStream>>, TokenStream> {
+        let mut defaults = Vec::new();
+
+        for field in fields.iter_mut() {
+            let mut default = None;
+
+            for attr in field.attrs.iter() {
+                if !attr.path.is_ident(DEFAULT_ATTR) {
+                    continue;
+                }
+
+                if !used {
+                    return Err(SyntaxError::new_spanned(
+                        attr,
+                        "`#[default]` is only used by `constructor`, `builder`, `log_on_create` and `tracing_event`",
+                    )
+                    .into_compile_error());
+                }
+
+                if default.is_some() {
+                    return Err(
+                        SyntaxError::new_spanned(attr, "`#[default]` is already defined")
+                            .into_compile_error(),
+                    );
+                }
+
+                let parser = |input: ParseStream| {
+                    input.parse::<Token![=]>()?;
+                    if input.is_empty() {
+                        return Err(input.error("expected default value"));
+                    }
+                    input.parse::<TokenStream>()
+                };
+
+                match parser.parse2(attr.tokens.clone()) {
+                    Ok(value) => default = Some(value),
+                    Err(_) => {
+                        return Err(SyntaxError::new_spanned(
+                            attr,
+                            "expected `#[default = <expression>]`",
+                        )
+                        .into_compile_error())
+                    }
+                }
+            }
+
+            field.attrs.retain(|attr| !attr.path.is_ident(DEFAULT_ATTR));
+            defaults.push(default);
+        }
+
+        Ok(defaults)
+    };
+
+    match data {
+        Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .map(|variant| take(&mut variant.fields))
+            .collect(),
+        Data::Struct(data) => Ok(vec![take(&mut data.fields)?]),
+        Data::Union(_) => Ok(Vec::new()),
+    }
+}
+
+fn has_on_create(error_args: &ErrorArgs) -> bool {
+    error_args.log_on_create.is_some() || error_args.tracing_event.is_some()
+}
+
+// Created errors are reported via `Display`, fields of `tracing` events via `Debug`
+fn on_create_where_clause<'a>(
+    generics: &Generics,
+    error_args: &ErrorArgs,
+    fields: impl Iterator<Item = &'a Field>,
+) -> Option<WhereClause> {
+    let mut generics = generics.clone();
+
+    if has_on_create(error_args) {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(Self: ::core::fmt::Display));
+    }
+
+    if error_args.tracing_event.is_some() && generics.type_params().next().is_some() {
+        for field in fields {
+            let ty = &field.ty;
+            generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#ty: ::core::fmt::Debug));
+        }
+    }
+
+    generics.where_clause
+}
+
+// Statements run by constructors with the created `error`
+fn on_create(
+    error_args: &ErrorArgs,
+    ident: &Ident,
+    variant: Option<&Ident>,
+    fields: &Fields,
+    sensitive: &[bool],
+) -> Option<TokenStream> {
+    if !has_on_create(error_args) {
+        return None;
+    }
+
+    let mut statements = TokenStream::new();
+
+    if error_args.log_on_create.is_some() {
+        statements.extend(quote!(::log::error!("{}", error);));
+    }
+
+    if error_args.tracing_event.is_some() {
+        statements.extend(tracing::event(ident, variant, fields, sensitive));
+    }
+
+    Some(statements)
+}
+
+fn builder_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    fields: &Fields,
+    context_map: bool,
+    defaults: &[Option<TokenStream>],
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let builder = format_ident!("{}Builder", ident);
+    let context = context_init(context_map);
+    let builder_doc = format!("Builder of [`{}`], see [`{}::builder`].", ident, ident);
+
+    let args = field_args(fields);
+    let slots = args
+        .iter()
+        .map(|(_, arg, ty)| quote!(#arg: ::core::option::Option<#ty>));
+    let empty_slots = args
+        .iter()
+        .map(|(_, arg, _)| quote!(#arg: ::core::option::Option::None));
+    let setters = args.iter().map(|(member, arg, ty)| {
+        let setter = format_ident!("with_{}", arg.to_string().trim_start_matches('_'));
+        let doc = format!("Sets `{}`.", quote!(#member));
+
+        quote! {
+            #[doc = #doc]
+            #vis fn #setter(mut self, #arg: #ty) -> Self {
+                self.#arg = ::core::option::Option::Some(#arg);
+                self
+            }
+        }
+    });
+    let values =
+        args.iter().enumerate().map(
+            |(idx, (member, arg, _))| match field_default(defaults, idx) {
+                Some(default) => quote!(#member: self.#arg.unwrap_or_else(|| #default)),
+                None => {
+                    let message = format!("`{}` is not set", quote!(#member));
+                    quote!(#member: self.#arg.expect(#message))
+                }
+            },
+        );
+
+    quote! {
+        #[doc = #builder_doc]
+        #[allow(dead_code)]
+        #vis struct #builder #impl_generics #where_clause {
+            #(#slots,)*
+        }
+
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns a builder of the error, which sets the fields one by one.
+            #vis fn builder() -> #builder #ty_generics {
+                #builder { #(#empty_slots,)* }
+            }
+        }
+
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            /// Creates the error.
+            ///
+            /// # Panics
+            ///
+            /// Panics if any of the fields without `#[default]` is not set.
+            #vis fn build(self) -> #ident #ty_generics {
+                #ident { #(#values,)* #context }
+            }
+        }
+    }
+}
+
+// The field added by `context_map` starts empty
+fn context_init(context_map: bool) -> TokenStream {
+    if context_map {
+        let context = format_ident!("{}", CONTEXT_FIELD);
+        quote!(#context: ::core::default::Default::default())
+    } else {
+        TokenStream::new()
+    }
+}
+
+fn check_context_map(
+    context_map: &kw::context_map,
+    error_args: &ErrorArgs,
+    fields: &Fields,
+    codegen: Codegen,
+) -> Result<(), TokenStream> {
+    let message = if let Codegen::Derive = codegen {
+        Some("`context_map` is not supported by `#[derive(Error)]`, use `#[Error]` instead")
+    } else if !cfg!(feature = "std") {
+        Some("`context_map` requires the `std` feature of justerror")
+    } else if let Some(Fmt::Json) = error_args.fmt {
+        Some("`context_map` can't be combined with `fmt = json`")
+    } else if let Fields::Named(_) = fields {
+        None
+    } else {
+        Some("`context_map` is only supported on structs with named fields")
+    };
+
+    if let Some(message) = message {
+        return Err(SyntaxError::new(context_map.span, message).into_compile_error());
+    }
+
+    let existing = fields.iter().find_map(|field| match &field.ident {
+        Some(ident) if ident == CONTEXT_FIELD => Some(ident),
+        _ => None,
+    });
+
+    match existing {
+        Some(ident) => Err(SyntaxError::new(
+            ident.span(),
+            "`context_map` adds the `context` field, rename this one",
+        )
+        .into_compile_error()),
+        None => Ok(()),
+    }
+}
+
+fn context_map_impl(ident: &Ident, vis: &Visibility, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let context = format_ident!("{}", CONTEXT_FIELD);
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Attaches the key-value pair, rendered after the fields.
+            #vis fn with_context(
+                mut self,
+                key: &'static str,
+                value: impl ::core::fmt::Display + ::core::marker::Send + ::core::marker::Sync + 'static,
+            ) -> Self {
+                self.#context.push(key, ::std::boxed::Box::new(value));
+                self
+            }
+        }
+
+        #[allow(deprecated)]
+        impl #impl_generics ::core::ops::AddAssign<(
+            &'static str,
+            ::std::boxed::Box<dyn ::core::fmt::Display + ::core::marker::Send + ::core::marker::Sync>,
+        )> for #ident #ty_generics #where_clause {
+            fn add_assign(
+                &mut self,
+                (key, value): (
+                    &'static str,
+                    ::std::boxed::Box<dyn ::core::fmt::Display + ::core::marker::Send + ::core::marker::Sync>,
+                ),
+            ) {
+                self.#context.push(key, value);
+            }
+        }
+    }
+}
+
+fn predicates_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    variants: &Punctuated<Variant, Token![,]>,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let predicates = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let predicate = format_ident!("is_{}", to_snake_case(&variant_ident.to_string()));
+        let doc = format!(
+            "Returns `true` if the error is [`{}::{}`].",
+            ident, variant_ident
+        );
+
+        let cfgs = cfg_attrs(&variant.attrs);
+
+        quote! {
+            #[doc = #doc]
+            #(#cfgs)*
+            #vis fn #predicate(&self) -> bool {
+                ::core::matches!(self, Self::#variant_ident { .. })
+            }
+        }
+    });
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#predicates)*
+        }
+    }
+}
+
+fn accessors_impl(
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    variants: &Punctuated<Variant, Token![,]>,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let accessors = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let accessor = format_ident!("as_{}", to_snake_case(&variant_ident.to_string()));
+        let doc = format!(
+            "Returns the fields of the error if it is [`{}::{}`].",
+            ident, variant_ident
+        );
+
+        let bindings: Vec<Ident> = (0..variant.fields.len())
+            .map(|idx| format_ident!("field_{}", idx))
+            .collect();
+        let tys = variant.fields.iter().map(|field| &field.ty);
+
+        let pattern = match &variant.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|field| &field.ident);
+                quote!(Self::#variant_ident { #(#names: #bindings),* })
+            }
+            Fields::Unnamed(_) => quote!(Self::#variant_ident(#(#bindings),*)),
+            Fields::Unit => quote!(Self::#variant_ident),
+        };
+
+        let (ret_ty, ret) = match bindings.len() {
+            1 => (quote!(#(&#tys)*), quote!(#(#bindings)*)),
+            _ => (quote!((#(&#tys),*)), quote!((#(#bindings),*))),
+        };
+
+        let cfgs = cfg_attrs(&variant.attrs);
+
+        quote! {
+            #[doc = #doc]
+            #(#cfgs)*
+            #vis fn #accessor(&self) -> ::core::option::Option<#ret_ty> {
+                match self {
+                    #pattern => ::core::option::Option::Some(#ret),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[allow(dead_code, deprecated)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#accessors)*
+        }
+    }
+}
+
+// Variants behind `#[cfg(...)]` may not exist, so neither should the code referring to them
+fn cfg_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .collect()
+}
+
+fn to_snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut snake = String::new();
+
+    for (idx, char) in chars.iter().enumerate() {
+        if char.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next = chars.get(idx + 1);
+            // `HttpError` -> `http_error`, `HTTPError` -> `http_error`
+            if prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(char.to_lowercase());
+    }
+
+    snake
+}
+
+fn derives(error_args: &ErrorArgs, attrs: &[Attribute], codegen: Codegen) -> Vec<TokenStream> {
+    // Derives listed on the type itself are kept as is, so they must not be repeated
+    let existing = existing_derives(attrs);
+
+    let mut derives = match codegen {
+        Codegen::Thiserror if !existing.iter().any(is_thiserror_derive) => {
+            vec![quote!(::justerror::__private::thiserror::Error)]
+        }
+        Codegen::Thiserror | Codegen::Standalone | Codegen::Derive => Vec::new(),
+    };
+
+    // `debug_same_as_display` implements `Debug` itself
+    if error_args.no_debug.is_none()
+        && error_args.debug_same_as_display.is_none()
+        && !existing.iter().any(is_debug_derive)
+    {
+        derives.push(quote!(Debug));
+    }
+
+    if let Some((_, paths)) = &error_args.derives {
+        // `thiserror::Error` and `Debug` (unless `no_debug` is set) are always derived
+        derives.extend(
+            paths
+                .iter()
+                .filter(|path| !is_debug_derive(path) && !is_thiserror_derive(path))
+                .filter(|path| error_args.hash.is_none() || !is_hash_derive(path))
+                .filter(|path| error_args.equality.is_none() || !is_eq_derive(path))
+                .filter(|path| error_args.clone.is_none() || !is_clone_derive(path))
+                .map(ToTokens::to_token_stream),
+        );
+    }
+
+    if error_args.hash.is_some() && !existing.iter().any(is_hash_derive) {
+        derives.push(quote!(Hash));
+    }
+
+    if error_args.clone.is_some() && !existing.iter().any(is_clone_derive) {
+        derives.push(quote!(Clone));
+    }
+
+    if cfg!(feature = "miette") && !existing.iter().any(is_diagnostic_derive) {
+        derives.push(miette::derive());
+    }
+
+    derives
+}
+
+fn existing_derives(attrs: &[Attribute]) -> Vec<Path> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .collect()
+}
+
+fn is_debug_derive(path: &Path) -> bool {
+    path.segments.last().is_some_and(|s| s.ident == "Debug")
+}
+
+fn is_clone_derive(path: &Path) -> bool {
+    path.segments.last().is_some_and(|s| s.ident == "Clone")
+}
+
+fn is_eq_derive(path: &Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|s| s.ident == "PartialEq" || s.ident == "Eq")
+}
+
+fn is_hash_derive(path: &Path) -> bool {
+    path.segments.last().is_some_and(|s| s.ident == "Hash")
+}
+
+// `#[error("...", args)]` as in `thiserror`
+fn is_thiserror_message(attr: &Attribute) -> bool {
+    attr.parse_args_with(|input: ParseStream| {
+        let _: LitStr = input.parse()?;
+        input.parse::<TokenStream>()
+    })
+    .is_ok()
+}
+
+// `#[diagnostic(...)]` goes next to `#[error(...)]`, messages of variants are in order
+fn push_diagnostic_attrs(error: &mut DeriveInput, messages: &[Message]) {
+    let attrs = messages.iter().map(|message| match message {
+        Message::Format(Output {
+            diagnostic: Some(diagnostic),
+            ..
+        }) => diagnostic.attr(),
+        Message::Format(_) | Message::Transparent => None,
+    });
+
+    match &mut error.data {
+        Data::Enum(data) => {
+            for (variant, attr) in data.variants.iter_mut().zip(attrs) {
+                variant.attrs.extend(attr);
+            }
+        }
+        Data::Struct(_) => {
+            let attrs: Vec<Attribute> = attrs.flatten().collect();
+            error.attrs.extend(attrs);
+        }
+        Data::Union(_) => (),
+    }
+}
+
+fn is_diagnostic_derive(path: &Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Diagnostic")
+}
+
+fn is_thiserror_derive(path: &Path) -> bool {
+    path.segments.len() == 2
+        && path.segments[0].ident == "thiserror"
+        && path.segments[1].ident == "Error"
+}
+
+// `proc_macro::Diagnostic` is nightly only, so warnings are emitted via the `deprecated` lint
+pub(crate) fn warning(span: Span, message: &str) -> TokenStream {
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #message)]
+            const WARNING: () = ();
+            WARNING
+        };
+    }
+}
+
+// The compiler reports invalid items on its own, the error of `syn` only adds the context
+fn parse_item(item: CompilerTokenStream) -> Result<DeriveInput, TokenStream> {
+    let item = TokenStream::from(item);
+
+    syn::parse2(item.clone()).map_err(|err| match validate::check_mixed_fields(item) {
+        Some(err) => err.into_compile_error(),
+        None => err.into_compile_error(),
+    })
+}
+
+/// See [crate documentation](https://docs.rs/justerror)
+#[proc_macro_attribute]
+#[allow(non_snake_case)]
+pub fn Error(args: CompilerTokenStream, item: CompilerTokenStream) -> CompilerTokenStream {
+    let mut error = match parse_item(item) {
+        Ok(error) => error,
+        Err(err) => return err.into(),
+    };
+
+    let error_args = if !args.is_empty() {
+        parse_macro_input!(args as ErrorArgs)
+    } else {
+        ErrorArgs::default()
+    };
+
+    // Impls of `thiserror` would trigger warnings on deprecated types,
+    // and a single format string can't switch the layout at runtime
+    let codegen = if cfg!(feature = "thiserror")
+        && !cfg!(feature = "standalone")
+        && !cfg!(feature = "runtime_fmt")
+        && error_args.deprecated.is_none()
+        && error_args.impl_display_only.is_none()
+        && error_args.strum_display.is_none()
+        && error_args.impl_std_error.is_none()
+    {
+        Codegen::Thiserror
+    } else {
+        Codegen::Standalone
+    };
+
+    match expand(error_args, &mut error, codegen) {
+        Ok(tokens) | Err(tokens) => tokens.into(),
+    }
+}
+
+/// See [crate documentation](https://docs.rs/justerror)
+#[proc_macro_derive(
+    DeriveError,
+    attributes(
+        justerror, error, fmt, skip, sensitive, default, label, reason, from, source, backtrace
+    )
+)]
+pub fn derive_error(item: CompilerTokenStream) -> CompilerTokenStream {
+    let mut error = match parse_item(item) {
+        Ok(error) => error,
+        Err(err) => return err.into(),
+    };
+
+    let error_args = match take_justerror_attr(&mut error.attrs) {
+        Ok(args) => args,
+        Err(err) => return err.into_compile_error().into(),
+    };
+
+    match expand(error_args, &mut error, Codegen::Derive) {
+        Ok(tokens) | Err(tokens) => tokens.into(),
+    }
+}
+
+// Derive macros can't add attributes to the type, so root arguments come from `#[justerror(...)]`
+fn deprecate(
+    attrs: &mut Vec<Attribute>,
+    note: &LitStr,
+    codegen: Codegen,
+) -> Result<(), TokenStream> {
+    if let Codegen::Derive = codegen {
+        return Err(SyntaxError::new(
+            note.span(),
+            "`deprecated` is not supported by `#[derive(Error)]`, use `#[deprecated]` instead",
+        )
+        .into_compile_error());
+    }
+
+    attrs.push(parse_quote!(#[deprecated(note = #note)]));
+
+    Ok(())
+}
+
+fn take_justerror_attr(attrs: &mut Vec<Attribute>) -> syn::Result<ErrorArgs> {
+    let mut error_args = None;
+
+    for attr in attrs.iter() {
+        if !attr.path.is_ident(JUSTERROR_ATTR) {
+            continue;
+        }
+
+        if error_args.is_some() {
+            return Err(SyntaxError::new_spanned(
+                attr,
+                "`#[justerror]` is already defined",
+            ));
+        }
+
+        error_args = Some(if attr.tokens.is_empty() {
+            ErrorArgs::default()
+        } else {
+            attr.parse_args::<ErrorArgs>()?
+        });
+    }
+
+    attrs.retain(|attr| !attr.path.is_ident(JUSTERROR_ATTR));
+
+    Ok(error_args.unwrap_or_default())
+}
+
+#[derive(Clone, Copy)]
+enum Codegen {
+    // `#[Error]` decorates the type with `#[error(...)]` attributes and derives `thiserror::Error`
+    Thiserror,
+    // `#[Error]` without `thiserror` feature or with `standalone` one implements `Display` and `Error` itself
+    Standalone,
+    // `#[derive(Error)]` can't modify the type, so it implements `Display` and `Error` as well
+    Derive,
+}
+
+// Display message of a struct or a single variant
+enum Message {
+    Transparent,
+    Format(Output),
+}
+
+fn expand(
+    error_args: ErrorArgs,
+    error: &mut DeriveInput,
+    codegen: Codegen,
+) -> Result<TokenStream, TokenStream> {
+    if let Codegen::Standalone | Codegen::Derive = codegen {
+        standalone::check(&error_args, &error.data, codegen)?;
+    }
+
+    if let Err(err) = error_args.check_root_level() {
+        return Err(err.into_compile_error());
+    }
+
+    if let Some(note) = &error_args.deprecated {
+        deprecate(&mut error.attrs, note, codegen)?;
+    }
+
+    let mut items = TokenStream::new();
+    let mut messages = Vec::new();
+    let sensitive_fields = tracing::sensitive_fields(&error.data);
+
+    let construction = error_args.constructor.is_some()
+        || error_args.builder.is_some()
+        || has_on_create(&error_args);
+    let defaults = take_defaults(&mut error.data, construction)?;
+
+    match &mut error.data {
+        Data::Enum(data) => {
+            if let Err(err) = error_args.check_enum_level() {
+                return Err(err.into_compile_error());
+            }
+
+            let mut variant_codes = Vec::new();
+            let mut variant_exit_codes = Vec::new();
+            let mut variant_categories = Vec::new();
+
+            check_from_types(&data.variants)?;
+            let mut used_codes = HashMap::new();
+
+            let mut transparent_types = Vec::new();
+
+            for (variant_idx, variant) in data.variants.iter_mut().enumerate() {
+                let mut variant_error_attr: Option<(usize, ErrorArgs)> = None;
+
+                for (idx, attr) in &mut variant.attrs.iter().enumerate() {
+                    if attr.path.is_ident(ERROR_ATTR) {
+                        if is_thiserror_message(attr) {
+                            return Err(SyntaxError::new_spanned(
+                                attr,
+                                "`#[error(\"...\")]` of thiserror conflicts with the message of justerror, use `#[error(raw = \"...\")]` instead",
+                            )
+                            .into_compile_error());
+                        }
+
+                        if variant_error_attr.is_some() {
+                            return Err(SyntaxError::new_spanned(
+                                attr,
+                                "`#[error]` is already defined, arguments must be in a single attribute",
+                            )
+                            .into_compile_error());
+                        }
+
+                        let error_args = match attr.parse_args_with(ErrorArgs::parse_variant_args) {
+                            Ok(args) => args,
+                            Err(err) => return Err(err.into_compile_error()),
+                        };
+                        for arg in &error_args.unknown {
+                            items.extend(warning(
+                                arg.span(),
+                                &format!(
+                                    "unknown justerror attribute argument `{}`, ignoring",
+                                    arg
+                                ),
+                            ));
+                        }
+                        variant_error_attr = Some((idx, error_args));
+                    }
+                }
+
+                let (variant_error_attr_idx, mut variant_error_args) = match variant_error_attr {
+                    Some((idx, args)) => (Some(idx), Some(args)),
+                    None => (None, None),
+                };
+
+                if let Some(idx) = variant_error_attr_idx {
+                    variant.attrs.remove(idx);
+                }
+
+                let fields_format = take_fields_format(&mut variant.attrs)?;
+
+                // Explicit `desc` takes precedence over doc comments, `raw` and `transparent` skip them
+                if let Some(doc) = extract_doc_comment(&variant.attrs) {
+                    let args = variant_error_args.get_or_insert_with(ErrorArgs::default);
+                    if args.desc.is_none() && args.raw.is_none() && args.transparent.is_none() {
+                        args.desc = Some(Desc::Lit(LitStr::new(&doc, Span::call_site())));
+                    }
+                }
+
+                if let Some(variant_error_args) = &variant_error_args {
+                    if let Err(err) = variant_error_args.check_variant_level() {
+                        return Err(err.into_compile_error());
+                    }
+                }
+
+                if let Some(ErrorArgs {
+                    deprecated: Some(note),
+                    ..
+                }) = &variant_error_args
+                {
+                    deprecate(&mut variant.attrs, note, codegen)?;
+                }
+
+                let code = match &variant_error_args {
+                    Some(ErrorArgs {
+                        code: Some(code), ..
+                    }) => Some(*code),
+                    Some(_) | None => match error_args.code {
+                        Some(base) => match base.checked_add(variant_idx as u32) {
+                            Some(code) => Some(code),
+                            None => {
+                                return Err(SyntaxError::new_spanned(
+                                    &variant.ident,
+                                    "error code of this variant overflows `u32`",
+                                )
+                                .into_compile_error())
+                            }
+                        },
+                        None => None,
+                    },
+                };
+
+                if let Some(code) = code {
+                    if let Some(first) = used_codes.insert(code, variant.ident.clone()) {
+                        items.extend(warning(
+                            variant.ident.span(),
+                            &format!("error code E{} is already used by `{}`", code, first),
+                        ));
+                    }
+                }
+
+                let cfgs: Vec<Attribute> = cfg_attrs(&variant.attrs).into_iter().cloned().collect();
+                variant_codes.push((variant.ident.clone(), cfgs.clone(), code));
+
+                let exit_code = match &variant_error_args {
+                    Some(ErrorArgs {
+                        exit_code: Some((exit_code, span)),
+                        ..
+                    }) => {
+                        if error_args.termination.is_some() {
+                            if let Err(err) = check_termination_exit_code(*exit_code, *span) {
+                                return Err(err.into_compile_error());
+                            }
+                        }
+                        Some(*exit_code)
+                    }
+                    Some(_) | None => None,
+                };
+                variant_exit_codes.push((variant.ident.clone(), cfgs.clone(), exit_code));
+
+                let category = match (&error_args.category, &variant_error_args) {
+                    (
+                        _,
+                        Some(ErrorArgs {
+                            category: Some(category),
+                            ..
+                        }),
+                    )
+                    | (Some(category), Some(ErrorArgs { category: None, .. }) | None) => {
+                        Some(category.clone())
+                    }
+                    (None, Some(ErrorArgs { category: None, .. }) | None) => None,
+                };
+                variant_categories.push((variant.ident.clone(), cfgs, category.clone()));
+
+                let transparent = match &variant_error_args {
+                    Some(ErrorArgs {
+                        transparent: Some(_),
+                        ..
+                    }) => true,
+                    Some(_) | None => error_args.transparent.is_some(),
+                };
+
+                if let Some(fields_format) = &fields_format {
+                    let raw = match &variant_error_args {
+                        Some(ErrorArgs { raw, .. }) => raw.is_some(),
+                        None => false,
+                    };
+                    let json = matches!(
+                        Fmt::derive(&error_args, &variant_error_args, &None),
+                        Fmt::Json
+                    );
+
+                    if transparent || raw || json {
+                        return Err(SyntaxError::new(
+                            fields_format.span(),
+                            "`#[fmt(\"...\")]` of a variant can't be combined with `transparent`, `raw` or `fmt = json`",
+                        )
+                        .into_compile_error());
+                    }
+
+                    if let Some(ErrorArgs {
+                        aggregate: Some(_), ..
+                    }) = &variant_error_args
+                    {
+                        return Err(SyntaxError::new(
+                            fields_format.span(),
+                            "`#[fmt(\"...\")]` of a variant can't be combined with `aggregate`",
+                        )
+                        .into_compile_error());
+                    }
+
+                    check_field_refs(fields_format, "#[fmt]", &variant.fields)?;
+                }
+
+                if transparent {
+                    check_transparent_fields(&variant.fields, &variant.ident)?;
+
+                    if error_args.transparent.is_none() {
+                        transparent_types
+                            .extend(variant.fields.iter().map(|field| type_name(&field.ty)));
+                    }
+
+                    messages.push(Message::Transparent);
+                    continue;
+                }
+
+                if let Some(ErrorArgs { raw: Some(raw), .. }) = &variant_error_args {
+                    take_field_attrs(&mut variant.fields);
+
+                    messages.push(Message::Format(Output::raw(raw.value())));
+                    continue;
+                }
+
+                let mut output = Output::new(&error_args, &variant_error_args);
+
+                if let Some(code) = code {
+                    output.push_code(code);
+                }
+
+                let prefix = match (&error_args.prefix, &variant_error_args) {
+                    (
+                        _,
+                        Some(ErrorArgs {
+                            prefix: Some(prefix),
+                            ..
+                        }),
+                    )
+                    | (Some(prefix), Some(ErrorArgs { prefix: None, .. }) | None) => Some(prefix),
+                    (None, Some(ErrorArgs { prefix: None, .. }) | None) => None,
+                };
+
+                output.push_title(
+                    prefix.map(String::as_str),
+                    &error.ident,
+                    Some(&variant.ident),
+                    error_args.separator.as_ref(),
+                );
+                output.push_reason(&variant.fields)?;
+
+                for desc in error_args.desc.iter().chain(
+                    variant_error_args
+                        .as_ref()
+                        .and_then(|args| args.desc.as_ref()),
+                ) {
+                    check_desc_fields(desc, &variant.fields)?;
+                }
+
+                match (&error_args.desc, &variant_error_args) {
+                    (
+                        Some(error_desc),
+                        Some(ErrorArgs {
+                            desc: Some(variant_desc),
+                            ..
+                        }),
+                    ) => {
+                        output.push_desc(
+                            Some(&error.ident),
+                            error_desc,
+                            &error.ident,
+                            &variant.ident,
+                        );
+                        output.push_desc(
+                            Some(&variant.ident),
+                            variant_desc,
+                            &error.ident,
+                            &variant.ident,
+                        );
+                    }
+                    (Some(error_desc), Some(ErrorArgs { desc: None, .. }) | None) => {
+                        output.push_desc(None, error_desc, &error.ident, &variant.ident);
+                    }
+                    (
+                        None,
+                        Some(ErrorArgs {
+                            desc: Some(variant_desc),
+                            ..
+                        }),
+                    ) => {
+                        output.push_desc(None, variant_desc, &error.ident, &variant.ident);
+                    }
+                    (None, Some(ErrorArgs { desc: None, .. }) | None) => (),
+                };
+
+                if let (Some(_), Some(category)) = (&error_args.show_category, &category) {
+                    output.push_category(category);
+                }
+
+                match (&error_args.help, &variant_error_args) {
+                    (
+                        _,
+                        Some(ErrorArgs {
+                            help: Some(help), ..
+                        }),
+                    )
+                    | (Some(help), Some(ErrorArgs { help: None, .. }) | None) => {
+                        check_field_refs(help, "help", &variant.fields)?;
+                        output.push_help(help, &error.ident, &variant.ident)
+                    }
+                    (None, Some(ErrorArgs { help: None, .. }) | None) => (),
+                };
+
+                match (&fields_format, &variant_error_args) {
+                    (Some(format), _) => output.push_fields_format(format, &mut variant.fields)?,
+                    (
+                        None,
+                        Some(ErrorArgs {
+                            aggregate: Some(aggregate),
+                            ..
+                        }),
+                    ) => output.push_aggregate(aggregate, &mut variant.fields, &error_args)?,
+                    (None, _) => {
+                        output.push_fields(&mut variant.fields, &error_args, &variant_error_args)?
+                    }
+                }
+
+                match (&error_args.url, &variant_error_args) {
+                    (_, Some(ErrorArgs { url: Some(url), .. }))
+                    | (Some(url), Some(ErrorArgs { url: None, .. }) | None) => {
+                        output.push_url(url, code)
+                    }
+                    (None, Some(ErrorArgs { url: None, .. }) | None) => (),
+                };
+
+                messages.push(Message::Format(output));
+            }
+
+            // Root level `transparent` is a deliberate choice, unlike marking every variant
+            if !data.variants.is_empty() && transparent_types.len() == data.variants.len() {
+                items.extend(warning(
+                    error.ident.span(),
+                    &format!(
+                        "all {} variants of `{}` are transparent ({}), consider deriving `thiserror::Error` directly",
+                        transparent_types.len(),
+                        error.ident,
+                        transparent_types.join(", ")
+                    ),
+                ));
+            }
+
+            if variant_codes.iter().any(|(_, _, code)| code.is_some()) {
+                let arms = variant_codes.iter().map(|(ident, cfgs, code)| match code {
+                    Some(code) => {
+                        quote!(#(#cfgs)* Self::#ident { .. } => ::core::option::Option::Some(#code))
+                    }
+                    None => quote!(#(#cfgs)* Self::#ident { .. } => ::core::option::Option::None),
+                });
+
+                items.extend(error_code_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(match *self { #(#arms,)* }),
+                ));
+            }
+
+            if let Some(path) = &error_args.impl_error_code {
+                let mut arms = Vec::new();
+
+                for (ident, cfgs, code) in &variant_codes {
+                    match code {
+                        Some(code) => arms.push(quote!(#(#cfgs)* Self::#ident { .. } => #code)),
+                        None => {
+                            return Err(SyntaxError::new_spanned(
+                                ident,
+                                "`impl_error_code` requires a code for every variant, set `code` on this variant or at the root level",
+                            )
+                            .into_compile_error())
+                        }
+                    }
+                }
+
+                items.extend(error_code_trait_impl(
+                    path,
+                    &error.ident,
+                    &error.generics,
+                    quote!(match *self { #(#arms,)* }),
+                ));
+            }
+
+            let exit_code = error_args.exit_code.is_some()
+                || variant_exit_codes.iter().any(|(_, _, code)| code.is_some());
+
+            if exit_code {
+                // Without explicit codes, errors exit with `1`, same as `main` returning `Err`
+                let default = error_args.exit_code.map_or(1, |(code, _)| code);
+                let arms = variant_exit_codes.iter().map(|(ident, cfgs, code)| {
+                    let code = code.unwrap_or(default);
+                    quote!(#(#cfgs)* Self::#ident { .. } => #code)
+                });
+
+                items.extend(exit_code_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(match *self { #(#arms,)* }),
+                ));
+            }
+
+            if error_args.termination.is_some() {
+                items.extend(termination_impl(&error.ident, &error.generics, exit_code));
+            }
+
+            if error_args.category.is_some()
+                || variant_categories
+                    .iter()
+                    .any(|(_, _, category)| category.is_some())
+            {
+                let arms = variant_categories.iter().map(|(ident, cfgs, category)| {
+                    let category = category.as_ref().map_or("", String::as_str);
+                    quote!(#(#cfgs)* Self::#ident { .. } => #category)
+                });
+
+                items.extend(category_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(match *self { #(#arms,)* }),
+                ));
+            }
+
+            if has_on_create(&error_args) {
+                items.extend(variant_constructors_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    &data.variants,
+                    &error_args,
+                    &sensitive_fields,
+                    &defaults,
+                ));
+            }
+
+            if error_args.predicates.is_some() {
+                items.extend(predicates_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    &data.variants,
+                ));
+            }
+
+            if error_args.accessors.is_some() {
+                items.extend(accessors_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    &data.variants,
+                ));
+            }
+
+            if error_args.variant_name.is_some() {
+                let arms = data.variants.iter().map(|variant| {
+                    let ident = &variant.ident;
+                    let cfgs = cfg_attrs(&variant.attrs);
+                    let name = ident.to_string();
+                    quote!(#(#cfgs)* Self::#ident { .. } => #name)
+                });
+
+                items.extend(variant_name_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(match *self { #(#arms,)* }),
+                ));
+            }
+        }
+        Data::Struct(data) => {
+            if let Err(err) = error_args.check_struct_level() {
+                return Err(err.into_compile_error());
+            }
+
+            if let Some(context_map) = &error_args.context_map {
+                check_context_map(context_map, &error_args, &data.fields, codegen)?;
+            }
+
+            if error_args.transparent.is_some() {
+                check_transparent_fields(&data.fields, &error.ident)?;
+
+                messages.push(Message::Transparent);
+            } else {
+                let mut output = Output::new(&error_args, &None);
+
+                if let Some(code) = error_args.code {
+                    output.push_code(code);
+                }
+
+                // Structs have no variant, so `separator` has nothing to separate
+                output.push_title(error_args.prefix.as_deref(), &error.ident, None, None);
+                output.push_reason(&data.fields)?;
+
+                if let Some(desc) = &error_args.desc {
+                    check_desc_fields(desc, &data.fields)?;
+                    output.push_desc(None, desc, &error.ident, &error.ident);
+                }
+
+                if let (Some(_), Some(category)) = (&error_args.show_category, &error_args.category)
+                {
+                    output.push_category(category);
+                }
+
+                if let Some(help) = &error_args.help {
+                    check_field_refs(help, "help", &data.fields)?;
+                    output.push_help(help, &error.ident, &error.ident);
+                }
+
+                output.push_fields(&mut data.fields, &error_args, &None)?;
+
+                if error_args.context_map.is_some() {
+                    output.push_context();
+                }
+
+                if let Some(url) = &error_args.url {
+                    output.push_url(url, error_args.code);
+                }
+
+                messages.push(Message::Format(output));
+            }
+
+            if let Some(code) = error_args.code {
+                items.extend(error_code_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(::core::option::Option::Some(#code)),
+                ));
+            }
+
+            if let Some(path) = &error_args.impl_error_code {
+                let code = match error_args.code {
+                    Some(code) => code,
+                    None => {
+                        return Err(SyntaxError::new_spanned(
+                            path,
+                            "`impl_error_code` requires `code`",
+                        )
+                        .into_compile_error())
+                    }
+                };
+
+                items.extend(error_code_trait_impl(
+                    path,
+                    &error.ident,
+                    &error.generics,
+                    quote!(#code),
+                ));
+            }
+
+            if let Some(category) = &error_args.category {
+                items.extend(category_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(#category),
+                ));
+            }
+
+            if let Some((exit_code, _)) = error_args.exit_code {
+                items.extend(exit_code_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(#exit_code),
+                ));
+            }
+
+            if error_args.termination.is_some() {
+                let exit_code = error_args.exit_code.is_some();
+                items.extend(termination_impl(&error.ident, &error.generics, exit_code));
+            }
+
+            if error_args.variant_name.is_some() {
+                let name = error.ident.to_string();
+                items.extend(variant_name_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    quote!(#name),
+                ));
+            }
+
+            let context_map = error_args.context_map.is_some();
+
+            if error_args.constructor.is_some() || has_on_create(&error_args) {
+                items.extend(constructor_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    &data.fields,
+                    &error_args,
+                    &sensitive_fields[0],
+                    &defaults[0],
+                ));
+            }
+
+            if error_args.builder.is_some() {
+                items.extend(builder_impl(
+                    &error.ident,
+                    &error.vis,
+                    &error.generics,
+                    &data.fields,
+                    context_map,
+                    &defaults[0],
+                ));
+            }
+
+            if context_map {
+                items.extend(context_map_impl(&error.ident, &error.vis, &error.generics));
+
+                if let Fields::Named(fields) = &mut data.fields {
+                    let vis = &error.vis;
+                    let context = format_ident!("{}", CONTEXT_FIELD);
+                    fields.named.push(Field {
+                        attrs: Vec::new(),
+                        vis: vis.clone(),
+                        ident: Some(context),
+                        colon_token: Some(Default::default()),
+                        ty: parse_quote!(::justerror::ContextMap),
+                    });
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(SyntaxError::new(
+                data.union_token.span,
+                "Untagged unions are not supported by the Error macro. Use an enum instead.",
+            )
+            .to_compile_error())
+        }
+    }
+
+    if let Some(result_alias) = &error_args.result_alias {
+        items.extend(result_alias_item(result_alias, error));
+    }
+
+    if error_args.send_sync.is_some() {
+        items.extend(send_sync_assertion(error));
+    }
+
+    if let Some(hash) = &error_args.hash {
+        items.extend(hash_assertion(hash, error));
+    }
+
+    if error_args.clone.is_some() {
+        items.extend(fields_assertion(error, quote!(::core::clone::Clone)));
+    }
+
+    if error_args.sources.is_some() {
+        items.extend(sources_impl(&error.ident, &error.vis, &error.generics));
+    }
+
+    if error_args.debug_same_as_display.is_some() {
+        items.extend(debug_impl(&error.ident, &error.generics));
+    }
+
+    if error_args.equality.is_some() {
+        items.extend(equality_impl(error));
+    }
+
+    if let Codegen::Derive = codegen {
+        let display_impl = error_args.strum_display.is_none();
+        items.extend(standalone::impls(error, &messages, display_impl, true));
+        return Ok(items);
+    }
+
+    // `source()` of `thiserror` refers to its `AsDynError` helper by a bare `thiserror::` path as well,
+    // so types with sources implement `Display` and `Error` themselves, unless `thiserror::Error` is derived
+    let codegen = match codegen {
+        Codegen::Thiserror
+            if standalone::has_sources(&error.data, &messages)
+                && !existing_derives(&error.attrs)
+                    .iter()
+                    .any(is_thiserror_derive) =>
+        {
+            Codegen::Standalone
+        }
+        codegen => codegen,
+    };
+
+    let derives = derives(&error_args, &error.attrs, codegen);
+
+    if cfg!(feature = "miette") {
+        push_diagnostic_attrs(error, &messages);
+    }
+
+    if let Codegen::Standalone = codegen {
+        let error_impl = error_args.impl_display_only.is_none();
+        if !error_impl {
+            items.extend(standalone::ignored_attrs_warnings(&error.data));
+        }
+        let display_impl = error_args.strum_display.is_none();
+        items.extend(standalone::impls(
+            error,
+            &messages,
+            display_impl,
+            error_impl,
+        ));
+        standalone::take_field_attrs(&mut error.data);
+
+        return Ok(quote! {
+          #[derive(#(#derives),*)]
+          #error
+          #items
+        });
+    }
+
+    match &mut error.data {
+        Data::Enum(data) => {
+            for (variant, message) in data.variants.iter_mut().zip(&messages) {
+                let args = display_args(message, &variant.fields);
+                variant
+                    .attrs
+                    .push(parse_quote!(#[error(#message #(, #args)*)]));
+            }
+        }
+        Data::Struct(data) => {
+            for message in &messages {
+                let args = display_args(message, &data.fields);
+                error
+                    .attrs
+                    .push(parse_quote!(#[error(#message #(, #args)*)]));
+            }
+        }
+        Data::Union(_) => (),
+    }
+
+    Ok(quote! {
+      #[derive(#(#derives),*)]
+      #error
+      #items
+    })
+}
@@ -0,0 +1,107 @@
+// `miette` feature: `miette::Diagnostic` is derived from the same arguments as the message
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Attribute, Field, Lit, LitStr, Meta, MetaNameValue, Type};
+
+use crate::LABEL_ATTR;
+
+#[derive(Default)]
+pub(crate) struct Diagnostic {
+    code: Option<u32>,
+    desc: Vec<String>,
+    help: Option<String>,
+    url: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn push_code(&mut self, code: u32) {
+        self.code = Some(code);
+    }
+
+    pub(crate) fn push_desc(&mut self, desc: &str) {
+        self.desc.push(desc.to_owned());
+    }
+
+    pub(crate) fn push_help(&mut self, help: &str) {
+        self.help = Some(help.to_owned());
+    }
+
+    pub(crate) fn push_url(&mut self, url: &str) {
+        self.url = Some(url.to_owned());
+    }
+
+    // Without `help`, the description is the most helpful text there is
+    pub(crate) fn attr(&self) -> Option<Attribute> {
+        let mut args = Vec::new();
+
+        if let Some(code) = self.code {
+            let code = format_ident!("E{}", code);
+            args.push(quote!(code(#code)));
+        }
+
+        let help = match &self.help {
+            Some(help) => Some(help.clone()),
+            None if !self.desc.is_empty() => Some(self.desc.join(": ")),
+            None => None,
+        };
+
+        if let Some(help) = help {
+            let help = LitStr::new(&help, Span::call_site());
+            args.push(quote!(help(#help)));
+        }
+
+        if let Some(url) = &self.url {
+            let url = LitStr::new(url, Span::call_site());
+            args.push(quote!(url(#url)));
+        }
+
+        if args.is_empty() {
+            return None;
+        }
+
+        Some(parse_quote!(#[diagnostic(#(#args),*)]))
+    }
+}
+
+pub(crate) fn derive() -> TokenStream {
+    quote!(::miette::Diagnostic)
+}
+
+// Spans are meaningless in the message, they are labels of the source code instead
+pub(crate) fn is_source_span(field: &Field) -> bool {
+    if !cfg!(feature = "miette") {
+        return false;
+    }
+
+    match &field.ty {
+        Type::Path(ty) => match ty.path.segments.last() {
+            Some(segment) => segment.ident == "SourceSpan",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// `#[label("...")]` of `miette`, as opposed to `#[label = "..."]` of justerror
+pub(crate) fn is_span_label(attr: &Attribute) -> bool {
+    cfg!(feature = "miette")
+        && attr.path.is_ident(LABEL_ATTR)
+        && matches!(attr.parse_meta(), Ok(Meta::List(_)))
+}
+
+pub(crate) fn convert_span_label(field: &mut Field) {
+    for attr in field.attrs.iter_mut() {
+        if !attr.path.is_ident(LABEL_ATTR) {
+            continue;
+        }
+
+        if let Ok(Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(label),
+            ..
+        })) = attr.parse_meta()
+        {
+            *attr = parse_quote!(#[label(#label)]);
+        }
+    }
+}
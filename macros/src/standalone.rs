@@ -0,0 +1,699 @@
+// `Display`, `Error` and `From` impls generated by `#[derive(Error)]` in place of `thiserror`
+
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error as SyntaxError, Field,
+    Fields, GenericArgument, Ident, LitStr, Member, PathArguments, Type, WherePredicate,
+};
+
+use crate::{
+    cfg_attrs, field_placeholders, is_backtrace_field, is_path_field, placeholder_field, warning,
+    Codegen, ErrorArgs, FormatValue, Message, Output, BACKTRACE_ATTR, CONTEXT_FIELD, FROM_ATTR,
+    SOURCE_ATTR,
+};
+
+pub(crate) fn check(
+    error_args: &ErrorArgs,
+    data: &Data,
+    codegen: Codegen,
+) -> Result<(), TokenStream> {
+    if let (Some((derives, _)), Codegen::Derive) = (&error_args.derives, codegen) {
+        return Err(SyntaxError::new_spanned(
+            derives,
+            "`derives` is not supported by `#[derive(Error)]`, list them next to `Error` instead",
+        )
+        .into_compile_error());
+    }
+
+    if let (Some(equality), Codegen::Derive) = (&error_args.equality, codegen) {
+        return Err(SyntaxError::new(
+            equality.span,
+            "`equality` is not supported by `#[derive(Error)]`, use `#[Error]` instead",
+        )
+        .into_compile_error());
+    }
+
+    if let (Some(clone), Codegen::Derive) = (&error_args.clone, codegen) {
+        return Err(SyntaxError::new(
+            clone.span,
+            "`clone` is not supported by `#[derive(Error)]`, list `Clone` next to `Error` instead",
+        )
+        .into_compile_error());
+    }
+
+    if let (Some(hash), Codegen::Derive) = (&error_args.hash, codegen) {
+        return Err(SyntaxError::new(
+            hash.span,
+            "`hash` is not supported by `#[derive(Error)]`, list `Hash` next to `Error` instead",
+        )
+        .into_compile_error());
+    }
+
+    if let (Some(debug_same_as_display), Codegen::Derive) =
+        (&error_args.debug_same_as_display, codegen)
+    {
+        return Err(SyntaxError::new(
+            debug_same_as_display.span,
+            "`debug_same_as_display` is not supported by `#[derive(Error)]`, use `#[Error]` instead",
+        )
+        .into_compile_error());
+    }
+
+    if let (Some(impl_display_only), Codegen::Derive) = (&error_args.impl_display_only, codegen) {
+        return Err(SyntaxError::new(
+            impl_display_only.span,
+            "`impl_display_only` is not supported by `#[derive(Error)]`, use `#[Error]` instead",
+        )
+        .into_compile_error());
+    }
+
+    let fields: Vec<&Field> = match data {
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    let backtrace_attr = fields
+        .iter()
+        .flat_map(|field| field.attrs.iter())
+        .find(|attr| attr.path.is_ident(BACKTRACE_ATTR));
+
+    match backtrace_attr {
+        Some(attr) => {
+            let message = match codegen {
+                Codegen::Derive => {
+                    "`#[backtrace]` is not supported by `#[derive(Error)]`, use `#[Error]` instead"
+                }
+                // `deprecated` types are implemented without `thiserror` as well
+                Codegen::Thiserror | Codegen::Standalone if error_args.deprecated.is_some() => {
+                    "`#[backtrace]` can't be combined with `deprecated`"
+                }
+                Codegen::Thiserror | Codegen::Standalone
+                    if error_args.impl_display_only.is_some() =>
+                {
+                    "`#[backtrace]` can't be combined with `impl_display_only`"
+                }
+                Codegen::Thiserror | Codegen::Standalone if cfg!(feature = "standalone") => {
+                    "`#[backtrace]` is not supported with the `standalone` feature of justerror"
+                }
+                Codegen::Thiserror | Codegen::Standalone => {
+                    "`#[backtrace]` requires the `thiserror` feature of justerror"
+                }
+            };
+
+            Err(SyntaxError::new_spanned(attr, message).into_compile_error())
+        }
+        None => Ok(()),
+    }
+}
+
+// Sources are exposed through `AsDynError`, including the single field of transparent messages
+pub(crate) fn has_sources(data: &Data, messages: &[Message]) -> bool {
+    let fields: Vec<&Fields> = match data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| &variant.fields)
+            .collect(),
+        Data::Struct(data) => vec![&data.fields],
+        Data::Union(_) => Vec::new(),
+    };
+
+    fields
+        .iter()
+        .zip(messages)
+        .any(|(fields, message)| match message {
+            Message::Transparent => true,
+            Message::Format(_) => source_idx(fields).is_some(),
+        })
+}
+
+// Without `display_impl`, `Display` is implemented elsewhere, e.g. by `strum::Display`
+pub(crate) fn impls(
+    error: &DeriveInput,
+    messages: &[Message],
+    display_impl: bool,
+    error_impl: bool,
+) -> TokenStream {
+    let arms: Vec<Arm> = match &error.data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .zip(messages)
+            .map(|(variant, message)| {
+                let ident = &variant.ident;
+                let cfgs = cfg_attrs(&variant.attrs);
+                Arm::new(quote!(Self::#ident), cfgs, &variant.fields, message)
+            })
+            .collect(),
+        Data::Struct(data) => messages
+            .iter()
+            .map(|message| Arm::new(quote!(Self), Vec::new(), &data.fields, message))
+            .collect(),
+        Data::Union(_) => return TokenStream::new(),
+    };
+
+    let ident = &error.ident;
+    let (impl_generics, ty_generics, _) = error.generics.split_for_impl();
+    let error_trait = error_trait();
+
+    // Like `thiserror`, fields of generic types must implement the traits formatting them
+    let mut display_generics = error.generics.clone();
+    let params: HashSet<&Ident> = error
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let mut bounds = Vec::new();
+    for arm in &arms {
+        for bound in arm.display_bounds(&params) {
+            let key = bound.to_token_stream().to_string();
+            if !bounds.contains(&key) {
+                bounds.push(key);
+                display_generics.make_where_clause().predicates.push(bound);
+            }
+        }
+    }
+    let where_clause = &display_generics.where_clause;
+
+    let mut error_generics = error.generics.clone();
+    error_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::core::fmt::Debug + ::core::fmt::Display));
+
+    // Like `thiserror`, sources of generic types must be errors that live long enough to be exposed
+    if error.generics.type_params().next().is_some() {
+        for ty in arms.iter().filter_map(Arm::source_ty) {
+            error_generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#ty: #error_trait + 'static));
+        }
+    }
+    let error_where_clause = &error_generics.where_clause;
+
+    let display_arms = arms.iter().map(Arm::display);
+    let source_arms = arms.iter().map(Arm::source);
+    let from_impls = arms.iter().filter_map(|arm| arm.impl_from(error));
+    let provide = provide_method(&arms);
+
+    // Matching on an empty enum via a reference is not exhaustive
+    let (display_body, source_body) = if arms.is_empty() {
+        (quote!(match *self {}), quote!(match *self {}))
+    } else {
+        (
+            quote!(match self { #(#display_arms,)* }),
+            quote!(match self { #(#source_arms,)* }),
+        )
+    };
+
+    // Deprecated types and variants are not deprecated for the generated impls
+    let display_impl = if display_impl {
+        quote! {
+            #[allow(deprecated)]
+            impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn fmt(&self, __formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    #display_body
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    if !error_impl {
+        return display_impl;
+    }
+
+    quote! {
+        #display_impl
+
+        #[allow(deprecated)]
+        impl #impl_generics #error_trait for #ident #ty_generics #error_where_clause {
+            #[allow(unused_variables)]
+            fn source(&self) -> ::core::option::Option<&(dyn #error_trait + 'static)> {
+                #[allow(unused_imports)]
+                use ::justerror::__private::AsDynError as _;
+                #source_body
+            }
+
+            #provide
+        }
+
+        #(#from_impls)*
+    }
+}
+
+fn write(output: &Output, fields: &Fields) -> TokenStream {
+    let format = bind_positional_args(&output.format_string());
+
+    let mut paths = Vec::new();
+    for (name, spec) in field_placeholders(&format) {
+        if let Some((_, field)) = placeholder_field(fields, &name) {
+            if spec.is_none() && is_path_field(field) && !paths.contains(&name) {
+                paths.push(name);
+            }
+        }
+    }
+    let paths = paths.iter().map(|name| {
+        let var = format_ident!("{}", name);
+        quote!(#var = #var.display())
+    });
+
+    let format = LitStr::new(&format, Span::call_site());
+    let args = output.args.iter().map(|arg| match &arg.value {
+        FormatValue::Field(Member::Named(ident)) => arg.wrap(quote!(#ident)),
+        FormatValue::Field(Member::Unnamed(idx)) => {
+            let binding = format_ident!("_{}", idx.index);
+            arg.wrap(quote!(#binding))
+        }
+        FormatValue::Const(path) => arg.wrap(quote!(#path)),
+        FormatValue::Context {
+            line_sep,
+            field_sep,
+        } => {
+            let context = format_ident!("{}", CONTEXT_FIELD);
+            quote!(::justerror::__private::Context(#context, #line_sep, #field_sep))
+        }
+    });
+    quote!(::core::write!(__formatter, #format #(, #args)* #(, #paths)*))
+}
+
+struct Arm<'a> {
+    path: TokenStream,
+    cfgs: Vec<&'a Attribute>,
+    fields: &'a Fields,
+    bindings: Vec<Ident>,
+    message: &'a Message,
+}
+
+impl<'a> Arm<'a> {
+    fn new(
+        path: TokenStream,
+        cfgs: Vec<&'a Attribute>,
+        fields: &'a Fields,
+        message: &'a Message,
+    ) -> Self {
+        let bindings = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{}", idx),
+            })
+            .collect();
+
+        Self {
+            path,
+            cfgs,
+            fields,
+            bindings,
+            message,
+        }
+    }
+
+    fn pattern(&self) -> TokenStream {
+        let path = &self.path;
+        let cfgs = &self.cfgs;
+        let bindings = &self.bindings;
+
+        match self.fields {
+            Fields::Named(_) => quote!(#(#cfgs)* #path { #(#bindings),* }),
+            Fields::Unnamed(_) => quote!(#(#cfgs)* #path(#(#bindings),*)),
+            Fields::Unit => quote!(#(#cfgs)* #path),
+        }
+    }
+
+    fn display(&self) -> TokenStream {
+        let pattern = self.pattern();
+
+        match self.message {
+            Message::Transparent => {
+                let inner = &self.bindings[0];
+                quote!(#pattern => ::core::fmt::Display::fmt(#inner, __formatter))
+            }
+            Message::Format(output) => {
+                let verbose = write(output, self.fields);
+                match &output.compact {
+                    Some(compact) => {
+                        let compact = write(compact, self.fields);
+                        quote! {
+                            #pattern => if ::justerror::config::is_compact() {
+                                #compact
+                            } else {
+                                #verbose
+                            }
+                        }
+                    }
+                    None => quote!(#pattern => #verbose),
+                }
+            }
+        }
+    }
+
+    fn source(&self) -> TokenStream {
+        let pattern = self.pattern();
+
+        match self.message {
+            Message::Transparent => {
+                let inner = &self.bindings[0];
+                let error_trait = error_trait();
+                quote!(#pattern => #error_trait::source(#inner.as_dyn_error()))
+            }
+            Message::Format(_) => match source_idx(self.fields) {
+                Some(idx) if is_option_field(self.fields, idx) => {
+                    let source = &self.bindings[idx];
+                    quote! {
+                        #pattern => ::core::option::Option::map(
+                            ::core::option::Option::as_ref(#source),
+                            |source| source.as_dyn_error(),
+                        )
+                    }
+                }
+                Some(idx) => {
+                    let source = &self.bindings[idx];
+                    quote!(#pattern => ::core::option::Option::Some(#source.as_dyn_error()))
+                }
+                None => quote!(#pattern => ::core::option::Option::None),
+            },
+        }
+    }
+
+    fn impl_from(&self, error: &DeriveInput) -> Option<TokenStream> {
+        let from_idx = self
+            .fields
+            .iter()
+            .position(|field| has_attr(field, FROM_ATTR))?;
+        let from_ty = &self.fields.iter().nth(from_idx)?.ty;
+
+        let path = &self.path;
+        let cfgs = &self.cfgs;
+        let values = self.fields.iter().enumerate().map(|(idx, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(idx.into()),
+            };
+
+            // `check_from_field` ensures other fields are backtraces
+            if idx == from_idx {
+                quote!(#member: source)
+            } else if is_option(&field.ty) {
+                quote! {
+                    #member: ::core::option::Option::Some(::std::backtrace::Backtrace::capture())
+                }
+            } else {
+                quote!(#member: ::std::backtrace::Backtrace::capture())
+            }
+        });
+
+        let ident = &error.ident;
+        let (impl_generics, ty_generics, where_clause) = error.generics.split_for_impl();
+
+        Some(quote! {
+            #(#cfgs)*
+            #[allow(deprecated)]
+            impl #impl_generics ::core::convert::From<#from_ty> for #ident #ty_generics #where_clause {
+                fn from(source: #from_ty) -> Self {
+                    #path { #(#values,)* }
+                }
+            }
+        })
+    }
+
+    // Optional sources are bound by their inner type
+    fn source_ty(&self) -> Option<&'a Type> {
+        let idx = match self.message {
+            Message::Transparent => 0,
+            Message::Format(_) => source_idx(self.fields)?,
+        };
+
+        self.fields
+            .iter()
+            .nth(idx)
+            .map(|field| option_inner(&field.ty).unwrap_or(&field.ty))
+    }
+
+    fn display_bounds(&self, params: &HashSet<&Ident>) -> Vec<WherePredicate> {
+        if params.is_empty() {
+            return Vec::new();
+        }
+
+        let outputs = match self.message {
+            Message::Transparent => {
+                return match self.fields.iter().next() {
+                    Some(field) if contains_param(&field.ty, params) => {
+                        let ty = &field.ty;
+                        vec![parse_quote!(#ty: ::core::fmt::Display)]
+                    }
+                    Some(_) | None => Vec::new(),
+                };
+            }
+            Message::Format(output) => {
+                let mut outputs = vec![output];
+                if let Some(compact) = &output.compact {
+                    outputs.push(compact);
+                }
+                outputs
+            }
+        };
+
+        let mut bounds = Vec::new();
+        for output in outputs {
+            let format = bind_positional_args(&output.format_string());
+            for (name, spec) in field_placeholders(&format) {
+                let field = match placeholder_field(self.fields, &name) {
+                    Some((_, field)) => field,
+                    None => continue,
+                };
+                if !contains_param(&field.ty, params) || spec.is_none() && is_path_field(field) {
+                    continue;
+                }
+
+                let ty = &field.ty;
+                let format_trait = format_trait(spec.as_ref().map_or("", String::as_str));
+                bounds.push(parse_quote!(#ty: ::core::fmt::#format_trait));
+            }
+        }
+
+        bounds
+    }
+
+    // Like `thiserror`, sources are asked first, then the backtrace of the error itself
+    fn provide(&self) -> TokenStream {
+        let pattern = self.pattern();
+
+        let backtrace_idx = match self.fields.iter().position(is_backtrace_field) {
+            Some(idx) => idx,
+            None => return quote!(#pattern => {}),
+        };
+
+        let source = source_idx(self.fields).map(|idx| {
+            let source = &self.bindings[idx];
+            let error_trait = error_trait();
+            if is_option_field(self.fields, idx) {
+                quote! {
+                    if let ::core::option::Option::Some(source) = #source {
+                        #error_trait::provide(source.as_dyn_error(), request);
+                    }
+                }
+            } else {
+                quote!(#error_trait::provide(#source.as_dyn_error(), request);)
+            }
+        });
+
+        let backtrace = &self.bindings[backtrace_idx];
+        let backtrace = if source_idx(self.fields) == Some(backtrace_idx) {
+            None
+        } else if is_option_field(self.fields, backtrace_idx) {
+            Some(quote! {
+                if let ::core::option::Option::Some(backtrace) = #backtrace {
+                    request.provide_ref::<::std::backtrace::Backtrace>(backtrace);
+                }
+            })
+        } else {
+            Some(quote!(request.provide_ref::<::std::backtrace::Backtrace>(#backtrace);))
+        };
+
+        quote!(#pattern => { #source #backtrace })
+    }
+}
+
+// Only `#[backtrace]` fields need `provide()`, which is nightly only
+fn provide_method(arms: &[Arm]) -> Option<TokenStream> {
+    let has_backtrace = arms
+        .iter()
+        .any(|arm| arm.fields.iter().any(is_backtrace_field));
+    if !cfg!(feature = "backtrace") || !has_backtrace {
+        return None;
+    }
+
+    let arms = arms.iter().map(Arm::provide);
+
+    Some(quote! {
+        #[allow(unused_variables)]
+        fn provide<'request>(&'request self, request: &mut ::std::error::Request<'request>) {
+            #[allow(unused_imports)]
+            use ::justerror::__private::AsDynError as _;
+            match self { #(#arms,)* }
+        }
+    })
+}
+
+// Same rules as `thiserror`: `#[source]` or `#[from]` field, otherwise a field named `source`
+fn source_idx(fields: &Fields) -> Option<usize> {
+    fields
+        .iter()
+        .position(|field| has_attr(field, SOURCE_ATTR) || has_attr(field, FROM_ATTR))
+        .or_else(|| {
+            fields.iter().position(|field| {
+                !is_backtrace_field(field)
+                    && field.ident.as_ref().is_some_and(|ident| ident == "source")
+            })
+        })
+}
+
+// Same traits as the format spec picks, e.g. `Debug` for `{a:?}`
+fn format_trait(spec: &str) -> Ident {
+    let name = match spec.chars().last() {
+        Some('?') => "Debug",
+        Some('o') => "Octal",
+        Some('x') => "LowerHex",
+        Some('X') => "UpperHex",
+        Some('p') => "Pointer",
+        Some('b') => "Binary",
+        Some('e') => "LowerExp",
+        Some('E') => "UpperExp",
+        Some(_) | None => "Display",
+    };
+
+    format_ident!("{}", name)
+}
+
+fn contains_param(ty: &Type, params: &HashSet<&Ident>) -> bool {
+    fn visit(tokens: TokenStream, params: &HashSet<&Ident>) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&ident),
+            TokenTree::Group(group) => visit(group.stream(), params),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+        })
+    }
+
+    visit(ty.to_token_stream(), params)
+}
+
+// Types are not resolved, so only `Option<T>` spelled as such is recognized, like `thiserror` does
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(ty) => ty.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    option_inner(ty).is_some()
+}
+
+fn is_option_field(fields: &Fields, idx: usize) -> bool {
+    fields
+        .iter()
+        .nth(idx)
+        .is_some_and(|field| is_option(&field.ty))
+}
+
+// Without the `Error` impl, there is nothing to expose the sources or to convert them
+pub(crate) fn ignored_attrs_warnings(data: &Data) -> TokenStream {
+    let fields: Vec<&Field> = match data {
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    fields
+        .iter()
+        .flat_map(|field| field.attrs.iter())
+        .filter_map(|attr| {
+            let name = if attr.path.is_ident(FROM_ATTR) {
+                FROM_ATTR
+            } else if attr.path.is_ident(SOURCE_ATTR) {
+                SOURCE_ATTR
+            } else {
+                return None;
+            };
+
+            Some(warning(
+                attr.path.span(),
+                &format!("`#[{}]` is ignored with `impl_display_only`", name),
+            ))
+        })
+        .collect()
+}
+
+// `#[Error]` re-emits the type, so attributes only known to `thiserror` have to go
+pub(crate) fn take_field_attrs(data: &mut Data) {
+    let fields: Vec<&mut Field> = match data {
+        Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .flat_map(|v| v.fields.iter_mut())
+            .collect(),
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    for field in fields {
+        field.attrs.retain(|attr| {
+            !attr.path.is_ident(FROM_ATTR)
+                && !attr.path.is_ident(SOURCE_ATTR)
+                && !attr.path.is_ident(BACKTRACE_ATTR)
+        });
+    }
+}
+
+// `core::error::Error` is stable since Rust 1.81, so `std` is used when available
+pub(crate) fn error_trait() -> TokenStream {
+    if cfg!(feature = "std") {
+        quote!(::std::error::Error)
+    } else {
+        quote!(::core::error::Error)
+    }
+}
+
+fn has_attr(field: &Field, name: &str) -> bool {
+    field.attrs.iter().any(|attr| attr.path.is_ident(name))
+}
+
+// Fields are bound to `_0`, `_1`, etc. in match arms, so `{0}` is rewritten to `{_0}`
+fn bind_positional_args(format: &str) -> String {
+    let mut result = String::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        result.push(char);
+
+        if char == '{' {
+            match chars.peek() {
+                Some('{') => result.extend(chars.next()),
+                Some(next) if next.is_ascii_digit() => result.push('_'),
+                _ => (),
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,85 @@
+// `tracing` feature: constructors emit an event with the created error and its fields
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, Fields, Ident, Member};
+
+use crate::{REDACTED, SENSITIVE_ATTR};
+
+// `#[sensitive]` is taken off the fields along the way, so it is collected beforehand
+pub(crate) fn sensitive_fields(data: &Data) -> Vec<Vec<bool>> {
+    let sensitive = |fields: &Fields| {
+        fields
+            .iter()
+            .map(|field| {
+                field
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path.is_ident(SENSITIVE_ATTR))
+            })
+            .collect()
+    };
+
+    match data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| sensitive(&variant.fields))
+            .collect(),
+        Data::Struct(data) => vec![sensitive(&data.fields)],
+        Data::Union(_) => Vec::new(),
+    }
+}
+
+// Fields are borrowed from the created `error`, keyed by their names or `_0`, `_1`, etc.
+pub(crate) fn event(
+    ident: &Ident,
+    variant: Option<&Ident>,
+    fields: &Fields,
+    sensitive: &[bool],
+) -> TokenStream {
+    let type_name = ident.to_string();
+
+    let (path, variant) = match variant {
+        Some(variant) => {
+            let name = variant.to_string();
+            (quote!(Self::#variant), Some(quote!(variant = #name,)))
+        }
+        None => (quote!(Self), None),
+    };
+
+    let mut bindings = Vec::new();
+    let mut values = Vec::new();
+
+    for (idx, field) in fields.iter().enumerate() {
+        let (binding, key) = match &field.ident {
+            Some(ident) => (quote!(#ident), ident.clone()),
+            None => {
+                let member = Member::Unnamed(idx.into());
+                let key = format_ident!("_{}", idx);
+                (quote!(#member: #key), key)
+            }
+        };
+
+        if sensitive.get(idx).cloned().unwrap_or(false) {
+            values.push(quote!(#key = #REDACTED));
+        } else {
+            bindings.push(binding);
+            values.push(quote!(#key = ?#key));
+        }
+    }
+
+    quote! {
+        match &error {
+            #path { #(#bindings,)* .. } => ::tracing::error!(
+                error = %error,
+                type_name = #type_name,
+                #variant
+                #(#values,)*
+                "error created"
+            ),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// Structural checks of items which `syn` fails to parse, to explain the error in justerror terms
+
+use proc_macro2::{Delimiter, Group, Ident, Spacing, TokenStream, TokenTree};
+use syn::Error as SyntaxError;
+
+pub(crate) fn check_mixed_fields(item: TokenStream) -> Option<SyntaxError> {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+
+    let kind = tokens.iter().position(|token| match token {
+        TokenTree::Ident(ident) => ident == "enum" || ident == "struct",
+        _ => false,
+    })?;
+
+    let name = match tokens.get(kind + 1) {
+        Some(TokenTree::Ident(ident)) => ident,
+        _ => return None,
+    };
+
+    // Const generic arguments may be braced as well, so the body is the last braced group
+    let body = tokens[kind..].iter().rev().find_map(|token| match token {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => Some(group),
+        _ => None,
+    })?;
+
+    match &tokens[kind] {
+        TokenTree::Ident(ident) if ident == "struct" => check_fields(name, body),
+        _ => {
+            let tokens: Vec<TokenTree> = body.stream().into_iter().collect();
+
+            tokens.windows(2).find_map(|pair| match pair {
+                [TokenTree::Ident(variant), TokenTree::Group(fields)]
+                    if fields.delimiter() == Delimiter::Brace =>
+                {
+                    check_fields(variant, fields)
+                }
+                _ => None,
+            })
+        }
+    }
+}
+
+fn check_fields(ident: &Ident, fields: &Group) -> Option<SyntaxError> {
+    let entries = split_entries(fields.stream());
+
+    if entries.iter().all(|entry| is_named(entry)) {
+        return None;
+    }
+
+    Some(SyntaxError::new(
+        ident.span(),
+        format!(
+            "`{}` mixes named and unnamed fields, fields must be either all named, e.g. `{} {{ a: u8 }}`, or all unnamed, e.g. `{}(u8)`",
+            ident, ident, ident
+        ),
+    ))
+}
+
+// Commas inside of generic arguments are not grouped, so angle brackets are tracked
+fn split_entries(fields: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut entries = vec![Vec::new()];
+    let mut depth = 0usize;
+    let mut arrow = false;
+
+    for token in fields {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    entries.push(Vec::new());
+                    arrow = false;
+                    continue;
+                }
+                _ => (),
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+
+        if let Some(entry) = entries.last_mut() {
+            entry.push(token);
+        }
+    }
+
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+// `#[attr]* pub(...)? name: Type`
+fn is_named(entry: &[TokenTree]) -> bool {
+    let mut tokens = entry.iter().peekable();
+
+    while let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() != '#' {
+            return false;
+        }
+        tokens.next();
+        tokens.next();
+    }
+
+    if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+        if ident == "pub" {
+            tokens.next();
+            if let Some(TokenTree::Group(group)) = tokens.peek() {
+                if group.delimiter() == Delimiter::Parenthesis {
+                    tokens.next();
+                }
+            }
+        }
+    }
+
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(_)), Some(TokenTree::Punct(colon))) => {
+            colon.as_char() == ':' && colon.spacing() == Spacing::Alone
+        }
+        _ => false,
+    }
+}
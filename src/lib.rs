@@ -8,6 +8,8 @@
 //! justerror = "0.1"
 //! ```
 //!
+//! There is no need to add `thiserror`: generated code uses the one re-exported by `justerror`, so your own `thiserror` dependency of any version, or an item named `thiserror`, doesn't interfere. Code generated by `thiserror` for `source()` refers to the crate by name, so types with sources implement `Display` and `Error` in the macro itself, with the same output.
+//!
 //! Add to `main.rs`:
 //!
 //! ```ignore
@@ -39,11 +41,54 @@
 //! // b: 42
 //! ```
 //!
-//! Macro accepts two optional arguments:
-//! - `desc`: string
-//! - `fmt`: `display` | `debug` | `"<custom format>"`
+//! Macro accepts optional arguments:
+//! - `desc`: string. `{type}` and `{variant}` are replaced with the type and variant names (`{{type}}` renders as `{type}`). Fields are referenced by name or index, e.g. `{path}` or `{0}`, and must exist. A path to a constant, e.g. `desc = errors::TIMEOUT`, is rendered via `Display` at runtime
+//! - `prefix`: string, prepended to the type name, e.g. `[myapp] EnumError::Foo` (variant level overrides root level)
+//! - `separator`: string, replaces `::` between the type and variant names in the title, e.g. `EnumError - Foo` (root level only, ignored by structs)
+//! - `fmt`: `display` | `debug` | `json` | `hex` | `upper_hex` | `octal` | `binary` | `pointer` | `truncate(<max length>)` | `"<custom format>"`. `truncate` caps the length of strings and is rejected on number fields, which would take it as precision
+//! - `help`: string, rendered as `hint: <help>` after the description (variant level overrides root level). Same format string as `desc`: `{type}`, `{variant}` and fields are replaced, literal braces are written as `{{` and `}}`
+//! - `url`: string, rendered as `see: <url>` at the end (variant level overrides root level). If it ends with `/`, the error code is appended
+//! - `transparent`: forwards the output and the source of the single field as is. Marking every variant of an enum as `transparent` triggers a warning, since `thiserror::Error` alone does the same, while the root level `transparent` doesn't
+//! - `predicates`: generates `is_<variant>()` methods (root level of enums only)
+//! - `accessors`: generates `as_<variant>()` methods returning references to the variant fields (root level of enums only)
+//! - `variant_name`: generates `variant_name()` method returning the name of the variant, or of the struct (root level only)
+//! - `sources`: generates `sources()` method returning an iterator over the error and its chain of sources (root level only)
+//! - `derives(...)`: additional derives, e.g. `derives(Clone, PartialEq)` (root level only)
+//! - `no_debug`: skips `Debug` derive, so it can be implemented by hand (root level only)
+//! - `debug_same_as_display`: implements `Debug` via `Display` instead of deriving it, so `{:?}` renders the same message (root level only)
+//! - `all_fields_debug`: formats every field with `Debug` as if it was marked with `#[fmt(debug)]`, so `fmt` of a variant doesn't override it, while `#[fmt(...)]` of a field does (root level only)
+//! - `anyhow_compat`: renders `anyhow::Error` fields with the whole chain of causes, e.g. `Failed to read config: No such file`, regardless of `fmt`. The type is recognized by its spelling, `anyhow::Error` or `::anyhow::Error` (root level only)
+//! - `nest`: indents multiline values of fields, e.g. wrapped errors, under the field name (root level only)
+//! - `send_sync`: checks at compile time that all fields are `Send + Sync`, assuming type parameters are (root level only)
+//! - `clone`: derives `Clone` and checks at compile time that each field implements it, pointing to the field which does not (root level only)
+//! - `hash`: derives `Hash` and checks at compile time that the type implements it, e.g. to use errors as `HashMap` keys (root level only)
+//! - `equality`: implements `PartialEq` and `Eq`. Fields are compared with `==` if all of them implement `Eq`, otherwise the `Debug` outputs of the errors are compared (root level only)
+//! - `deprecated`: string, marks the type or the variant as `#[deprecated]` without warnings from the generated code
+//! - `raw`: string, used as the whole format string of the variant instead of the generated one, e.g. `raw = "Failed to read {path}"` (variant level only, can't be combined with `desc` and `fmt`)
+//! - `aggregate`: renders the single field of the variant, e.g. `Vec<E>` or `Vec<Box<dyn Error>>`, as a numbered list of errors, `1. <first error>`, `2. <second error>`, etc. (variant level only, can't be combined with `raw`, `fmt` and `#[fmt("...")]`)
+//! - `result_alias`: string, generates `type <alias><T> = Result<T, Error>` (root level only)
+//! - `header`: string, replaces the `=== ↴` line above the fields (root level only, `""` removes it)
+//! - `sep`: string, replaces `: ` between field names and values (variant level overrides root level)
+//! - `compact`: renders the whole message on a single line, e.g. `EnumError::Bar a=Hey! b=42` (`compact = false` opts a variant out)
+//! - `title`: boolean. `title = false` omits the title line, i.e. the code, the prefix and the names, so the message starts with `desc` or the fields (`title = true` opts a variant back in). `no_title` is a shorthand for `title = false` at the root level
+//! - `code`: integer, prepends `[E<code>]` to the message and generates `error_code()` method. At the root level of an enum, it is a base code: variants without own code get `<code> + <variant index>`
+//! - `impl_error_code`: string, root level only, path to a trait of your own with `fn code(&self) -> u32`, implemented with the `code` of the error (or of each variant, all of them must have one)
+//! - `exit_code`: integer, generates `exit_code()` method for CLI apps, returning the code of the variant, the code of the root level, or `1`
+//! - `termination`: implements `std::process::Termination`, so `main` returning the error prints its message to stderr and exits with `exit_code()`, or `1` without `exit_code`. Exit codes must be in range `1..=255`. `main` returning `Result<(), Error>` prints `Debug` output regardless, `debug_same_as_display` makes it the message (root level only, requires the `std` feature)
+//! - `category`: string, generates `category()` method returning the category of the variant or of the root level, or `""` (variant level overrides root level)
+//! - `show_category`: renders the category as `category: <category>` after the description (root level only)
+//! - `impl_display_only`: implements only `Display`, without `Error` and `From` impls. `#[from]` and `#[source]` are ignored with a warning (root level only)
+//! - `strum_display`: skips `Display` impl and the message arguments, so `Display` can be derived by `strum::Display` with `#[strum(to_string = "...")]` on the variants, or implemented by hand. `Error` and `From` impls are still generated (root level only)
+//! - `impl_std_error`: implements `Display` and `Error` in the macro itself instead of deriving `thiserror::Error`, same as the `standalone` feature does for every type. `source()` returns the `#[source]` or `#[from]` field. The `thiserror` dependency itself is only removed by disabling its feature (root level only)
+//! - `constructor`: generates `new()` method taking all fields in order, named like the fields or `_0`, `_1`, etc. Fields marked with `#[default = <expr>]` are left out and set to the expression, e.g. `#[default = Duration::from_secs(30)]` (structs only)
+//! - `builder`: generates `builder()` method returning `<Name>Builder` with `with_<field>()` setters and `build()` method, which panics if a field is not set, unless it has `#[default = <expr>]` (structs only)
+//! - `context_map`: adds `context: justerror::ContextMap` field and generates `with_context(key, value)` method. Attached pairs are rendered after the fields (structs with named fields only)
+//! - `log_on_create`: generates constructors, `new_<variant>()` of enums and `new()` of structs, which log the message of the created error with `log::error!` (root level only, requires the `log` feature)
+//! - `tracing_event`: generates constructors like `log_on_create` does, which emit `tracing::error!` event with the created error as `error`, `type_name`, `variant` and the values of the fields, named like the fields or `_0`, `_1`, etc. Values of `#[sensitive]` fields are redacted (root level only, requires the `tracing` feature)
+//!
+//! Unknown arguments at the variant level are ignored with a warning, so attributes written for newer versions still compile.
 //!
-//! Both can be applied at the root level.
+//! `desc` and `fmt` can be applied at the root level.
 //!
 //! ```rust
 //! # use justerror::Error;
@@ -75,423 +120,540 @@
 //! }
 //! ```
 //!
+//! Instead of a custom string, the format spec of a field can be built from named arguments: `width`, `precision`, `align` (`left`, `right` or `center`) and `fill`, e.g. `#[fmt(fill = '0', width = 5)]` is the same as `#[fmt("05")]`.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! struct StructError {
+//!     #[fmt(width = 5, align = right)]
+//!     a: &'static str,
+//!     #[fmt(precision = 2)]
+//!     b: f64,
+//! }
+//! ```
+//!
+//! Fields marked with `#[skip]` or `#[fmt(skip)]` are left out of the output, as well as `PhantomData` markers.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum EnumError {
+//!     Foo {
+//!         a: usize,
+//!         #[skip]
+//!         b: Vec<u8>,
+//!     },
+//! }
+//! ```
+//!
+//! `#[fmt("...")]` on a variant replaces its fields block with a format string referencing the fields by name, or by position for unnamed fields, where `{}` refers to the fields in order.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum EnumError {
+//!     #[fmt("used: {used}/{total} MB")]
+//!     Quota { used: u32, total: u32 },
+//!     #[fmt("{}..{}")]
+//!     Range(usize, usize),
+//! }
+//! ```
+//!
+//! Fields marked with `#[label = "..."]` are displayed under the given label instead of the field name.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! struct StructError {
+//!     #[label = "request timeout (ms)"]
+//!     req_timeout_ms: usize,
+//! }
+//! ```
+//!
+//! The field marked with `#[reason]` is displayed in the title line, e.g. `EnumError::Foo: Service is unavailable`, instead of the fields below. Only one field of a variant can be the reason.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum EnumError {
+//!     Foo {
+//!         #[reason]
+//!         message: String,
+//!         status: usize,
+//!     },
+//! }
+//! ```
+//!
+//! Values of fields marked with `#[sensitive]` are replaced with `[REDACTED]`, so such fields don't need to implement `Display`.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum EnumError {
+//!     Login {
+//!         user: String,
+//!         #[sensitive]
+//!         password: String,
+//!     },
+//! }
+//! ```
+//!
+//! Other attributes of the type, including `#[derive(...)]`, are kept in place. If `Debug` or `thiserror::Error` is already derived there, it is not derived again. Derives written above `#[Error]` are expanded before the macro and can't be detected, so either place them below it or add `no_debug`.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! #[derive(Debug, serde::Serialize)]
+//! enum EnumError {
+//!     Foo { a: String },
+//! }
+//! ```
+//!
+//! Fields may borrow data, the lifetimes have to be declared on the type as usual. Such errors are not `'static`, so they can't be returned as sources of other errors.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum ParseError<'a> {
+//!     InvalidToken { text: &'a str, position: usize },
+//! }
+//! ```
+//!
+//! Errors defined in a submodule are re-exported with `pub use` as usual. The output of an attribute macro replaces the type in place, so `#[Error]` can't add the re-export to the parent module.
+//!
+//! Doc comments on variants are used as `desc`, unless it is set explicitly.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error]
+//! enum EnumError {
+//!     /// The connection timed out
+//!     Timeout,
+//! }
+//! ```
+//!
+//! `transparent` can be applied to a newtype struct or to an enum where every variant has a single field. At the variant level it applies to a single variant with a single field.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error(transparent)]
+//! enum EnumError {
+//!     Io(std::io::Error),
+//! }
+//! ```
+//!
+//! `thiserror` field attributes like `#[from]` and `#[source]` are passed through as is.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error(desc = "My emum error description")]
+//! enum EnumError {
+//!     Io(#[from] std::io::Error),
+//! }
+//! ```
+//!
+//! On nightly, enable the `backtrace` feature to pass through `#[backtrace]` as well. Backtrace fields are not included in the output.
+//!
+//! ```toml
+//! justerror = { version = "0.1", features = ["backtrace"] }
+//! ```
+//!
+//! `fmt = json` renders the message as a single-line JSON object, e.g. for structured logs, without depending on `serde`. Integer and boolean fields are emitted as JSON values, other fields are rendered via `Display` (or `Debug` with `#[fmt(debug)]`) and escaped as JSON strings.
+//!
+//! ```rust
+//! # use justerror::Error;
+//! #[Error(fmt = json, code = 100)]
+//! enum EnumError {
+//!     Bar { a: &'static str, b: usize },
+//! }
+//!
+//! // {"type":"EnumError","variant":"Bar","code":100,"fields":{"a":"Hey!","b":42}}
+//! ```
+//!
+//! If you prefer derives, `justerror::derive::Error` accepts the same arguments via the `#[justerror(...)]` attribute on the type. It implements `Display`, `Error` and `From` itself, so `Debug` has to be derived explicitly. `derives` and `#[backtrace]` are not supported in this form, and generic parameters need bounds on the type itself.
+//!
+//! ```rust
+//! use justerror::derive::Error;
+//!
+//! #[derive(Debug, Error)]
+//! #[justerror(desc = "My emum error description")]
+//! enum EnumError {
+//!     #[error(desc = "Foo error")]
+//!     Foo,
+//!     Io(#[from] std::io::Error),
+//! }
+//! ```
+//!
+//! Without the default `std` feature the macro works in `no_std` crates. Without the default `thiserror` feature `Display` and `Error` are implemented by the macro itself, with the same output. `#[backtrace]` is not available.
+//!
+//! ```toml
+//! justerror = { version = "0.1", default-features = false }
+//! ```
+//!
+//! To avoid depending on `thiserror` in a `std` crate, disable the default features and enable `standalone`. It also makes the macro implement `Display` and `Error` itself when `thiserror` is enabled by another dependency. Bounds of generic parameters are inferred like `thiserror` does.
+//!
+//! ```toml
+//! justerror = { version = "0.1", default-features = false, features = ["std", "standalone"] }
+//! ```
+//!
+//! The `miette` feature derives `miette::Diagnostic` as well, `miette` itself has to be a dependency of your crate. `code`, `help` (or `desc` without it) and `url` are forwarded to `#[diagnostic(...)]`. Fields of type `SourceSpan` are left out of the message, their `#[label = "..."]` becomes the label of the span. `?` converts such errors into `miette::Report` via the impl of `miette` itself.
+//!
+//! ```toml
+//! justerror = { version = "0.1", features = ["miette"] }
+//! miette = "7.0"
+//! ```
+//!
+//! The `runtime_fmt` feature switches between the verbose and the compact layout at runtime, e.g. to keep multi-line messages in debug builds only. Messages with an explicit `compact` argument keep their layout. Like `standalone`, it makes the macro implement `Display` and `Error` itself.
+//!
+//! ```toml
+//! justerror = { version = "0.1", features = ["runtime_fmt"] }
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "runtime_fmt")]
+//! justerror::config::set_compact(!cfg!(debug_assertions));
+//! ```
+//!
+//! The `log` feature enables `log_on_create`, `log` itself has to be a dependency of your crate.
+//!
+//! ```toml
+//! justerror = { version = "0.1", features = ["log"] }
+//! log = "0.4"
+//! ```
+//!
+//! The `tracing` feature enables `tracing_event`, `tracing` itself has to be a dependency of your crate.
+//!
+//! ```toml
+//! justerror = { version = "0.1", features = ["tracing"] }
+//! tracing = "0.1"
+//! ```
+//!
 //! See [tests](tests/tests.rs) for more examples.
 
-extern crate proc_macro;
-
-use std::{
-    default::Default,
-    fmt::{self, Display},
-};
-
-use proc_macro::TokenStream as CompilerTokenStream;
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{
-    parse::{Parse, ParseStream},
-    parse_macro_input, parse_quote, Data, DeriveInput, Error as SyntaxError, Field, Fields, Ident,
-    Lit, Token,
-};
-
-const ERROR_ATTR: &str = "error";
-const FMT_ATTR: &str = "fmt";
-
-mod kw {
-    syn::custom_keyword!(desc);
-    syn::custom_keyword!(fmt);
-    syn::custom_keyword!(debug);
-    syn::custom_keyword!(display);
-}
-
-#[derive(Default, Debug)]
-struct ErrorArgs {
-    desc: Option<String>,
-    fmt: Option<Fmt>,
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[doc(inline)]
+pub use justerror_macros::Error;
+
+/// `#[derive(Error)]` flavour of the [`Error`](crate::Error) macro.
+///
+/// ```
+/// use justerror::derive::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[justerror(desc = "Failed to parse config")]
+/// struct ConfigError {
+///     path: String,
+/// }
+/// ```
+pub mod derive {
+    #[doc(inline)]
+    pub use justerror_macros::DeriveError as Error;
 }
 
-impl ErrorArgs {
-    fn parse_desc(input: ParseStream) -> syn::Result<String> {
-        let _: kw::desc = input.parse()?;
-        let _: Token![=] = input.parse()?;
-        let val: Lit = input.parse()?;
-
-        match val {
-            Lit::Str(str) => Ok(str.value()),
-            _ => Err(SyntaxError::new(val.span(), "`desc` must be a string")),
-        }
+/// Runtime switch of the layout, available with the `runtime_fmt` feature.
+///
+/// Messages without an explicit `compact` argument are rendered in the compact layout
+/// while [`is_compact`](config::is_compact) returns `true`.
+///
+/// ```
+/// justerror::config::set_compact(!cfg!(debug_assertions));
+/// ```
+#[cfg(feature = "runtime_fmt")]
+pub mod config {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static COMPACT: AtomicBool = AtomicBool::new(false);
+
+    /// Switches all messages to the compact (`true`) or the verbose (`false`) layout.
+    pub fn set_compact(compact: bool) {
+        COMPACT.store(compact, Ordering::Relaxed);
     }
 
-    fn parse_fmt(input: ParseStream) -> syn::Result<Fmt> {
-        let _: kw::fmt = input.parse()?;
-        let _: Token![=] = input.parse()?;
-        let val = input.parse::<Fmt>()?;
-
-        Ok(val)
+    /// Returns `true` if messages are rendered in the compact layout, `false` by default.
+    pub fn is_compact() -> bool {
+        COMPACT.load(Ordering::Relaxed)
     }
 }
 
-impl Parse for ErrorArgs {
-    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
-        let mut args = Self::default();
+/// Key-value pairs attached to errors generated with `context_map`.
+///
+/// ```
+/// use justerror::Error;
+///
+/// #[Error(context_map)]
+/// struct ConfigError {
+///     path: &'static str,
+/// }
+///
+/// let error = ConfigError {
+///     path: "config.toml",
+///     context: Default::default(),
+/// }
+/// .with_context("attempt", 1);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct ContextMap(Vec<(&'static str, Box<dyn std::fmt::Display + Send + Sync>)>);
+
+#[cfg(feature = "std")]
+impl ContextMap {
+    /// Appends the pair, keys are not deduplicated.
+    pub fn push(&mut self, key: &'static str, value: Box<dyn std::fmt::Display + Send + Sync>) {
+        self.0.push((key, value));
+    }
 
-        let lookahead = input.lookahead1();
+    /// Returns the pairs in the order they were attached.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (&'static str, &(dyn std::fmt::Display + Send + Sync))> {
+        self.0.iter().map(|(key, value)| (*key, &**value))
+    }
 
-        if lookahead.peek(kw::desc) {
-            let desc = Self::parse_desc(input)?;
-            args.desc = Some(desc);
-        } else if lookahead.peek(kw::fmt) {
-            let fmt = Self::parse_fmt(input)?;
-            args.fmt = Some(fmt);
-        } else {
-            return Err(lookahead.error());
-        }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 
-        if input.is_empty() {
-            return Ok(args);
-        } else {
-            input.parse::<Token![,]>()?;
-        }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
 
-        let lookahead = input.lookahead1();
+#[cfg(feature = "std")]
+impl std::fmt::Debug for ContextMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        struct Value<'a>(&'a (dyn std::fmt::Display + Send + Sync));
 
-        if lookahead.peek(kw::desc) {
-            if args.desc.is_some() {
-                return Err(SyntaxError::new(input.span(), "`desc` is already defined"));
-            }
-            let desc = Self::parse_desc(input)?;
-            args.desc = Some(desc);
-        } else if lookahead.peek(kw::fmt) {
-            if args.fmt.is_some() {
-                return Err(SyntaxError::new(input.span(), "`fmt` is already defined"));
+        impl<'a> std::fmt::Debug for Value<'a> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{:?}", self.0.to_string())
             }
-            let fmt = Self::parse_fmt(input)?;
-            args.fmt = Some(fmt);
-        } else {
-            return Err(lookahead.error());
         }
 
-        if input.is_empty() {
-            Ok(args)
-        } else {
-            Err(SyntaxError::new(
-                input.span(),
-                "`error` can't have more than 2 arguments",
-            ))
-        }
+        f.debug_map()
+            .entries(self.iter().map(|(key, value)| (key, Value(value))))
+            .finish()
     }
 }
 
-#[derive(Clone, Debug)]
-enum Fmt {
-    Display,
-    Debug,
-    Custom(String),
-}
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(not(feature = "std"))]
+    use core::error::Error;
+    #[cfg(feature = "std")]
+    use std::error::Error;
+
+    #[cfg(feature = "thiserror")]
+    pub use thiserror;
+
+    // Converts `#[source]` fields to `&dyn Error`, including unsized `dyn Error` in boxes
+    pub trait AsDynError<'a> {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a);
+    }
 
-impl Fmt {
-    fn derive(root: &ErrorArgs, variant: &Option<ErrorArgs>, field: &Option<Self>) -> Self {
-        match field {
-            Some(fmt) => fmt.to_owned(),
-            None => match variant {
-                Some(ErrorArgs {
-                    desc: _,
-                    fmt: Some(fmt),
-                }) => fmt.to_owned(),
-                Some(_) | None => match &root.fmt {
-                    Some(fmt) => fmt.to_owned(),
-                    None => Fmt::default(),
-                },
-            },
+    impl<'a, T: Error + 'a> AsDynError<'a> for T {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
         }
     }
-}
 
-impl Default for Fmt {
-    fn default() -> Self {
-        Fmt::Display
+    impl<'a> AsDynError<'a> for dyn Error + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
     }
-}
 
-impl Display for Fmt {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Fmt::Display => Ok(()),
-            Fmt::Debug => write!(f, ":#?"),
-            Fmt::Custom(fmt) => write!(f, ":{}", fmt),
+    impl<'a> AsDynError<'a> for dyn Error + Send + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
         }
     }
-}
 
-impl Parse for Fmt {
-    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
-        let fmt = match input.parse::<kw::debug>() {
-            Ok(_) => Fmt::Debug,
-            Err(_) => match input.parse::<kw::display>() {
-                Ok(_) => Fmt::Display,
-                Err(_) => match input.parse::<Lit>()? {
-                    Lit::Str(str) => Fmt::Custom(str.value()),
-                    lit => {
-                        return Err(SyntaxError::new(
-                            lit.span(),
-                            "`fmt` must be either `debug`, `display` or a custom string",
-                        ))
-                    }
-                },
-            },
-        };
-
-        Ok(fmt)
+    impl<'a> AsDynError<'a> for dyn Error + Send + Sync + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
     }
-}
 
-struct Output(String);
+    // Renders fields of `fmt = json` errors as JSON strings
+    pub struct JsonDisplay<T>(pub T);
 
-enum FieldIdentStyle {
-    Prefixed,
-    Unprefixed,
-}
+    impl<T: core::fmt::Display> core::fmt::Display for JsonDisplay<T> {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            use core::fmt::Write as _;
 
-impl Output {
-    fn new() -> Self {
-        Self(String::new())
+            f.write_char('"')?;
+            write!(JsonEscape(f), "{}", self.0)?;
+            f.write_char('"')
+        }
     }
 
-    fn push_title(&mut self, head: &Ident, tail: Option<&Ident>) {
-        let buf = &mut self.0;
+    pub struct JsonDebug<T>(pub T);
 
-        buf.push_str(&head.to_string());
+    impl<T: core::fmt::Debug> core::fmt::Display for JsonDebug<T> {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            use core::fmt::Write as _;
 
-        if let Some(tail) = tail {
-            buf.push_str("::");
-            buf.push_str(&tail.to_string());
+            f.write_char('"')?;
+            write!(JsonEscape(f), "{:?}", self.0)?;
+            f.write_char('"')
         }
     }
 
-    fn push_desc(&mut self, prefix: Option<&Ident>, desc: &str) {
-        let buf = &mut self.0;
+    // Rendered inside of a JSON string, e.g. a constant `desc`
+    pub struct JsonEscaped<T>(pub T);
 
-        buf.push('\n');
+    impl<T: core::fmt::Display> core::fmt::Display for JsonEscaped<T> {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            use core::fmt::Write as _;
 
-        if let Some(prefix) = prefix {
-            buf.push_str(&prefix.to_string());
-            buf.push_str(": ");
+            write!(JsonEscape(f), "{}", self.0)
         }
-
-        buf.push_str(desc);
     }
 
-    fn push_debug_title(&mut self) {
-        let buf = &mut self.0;
+    // Fields compared by `equality`: `==` is picked via autoref when the field is `Eq`, so the
+    // generated `Eq` holds, otherwise the comparison is left to the caller
+    pub struct EqProbe<'a, T: ?Sized>(pub &'a T, pub &'a T);
 
-        buf.push('\n');
-        buf.push_str("=== ↴");
+    pub trait EqViaEq {
+        fn justerror_eq(&self) -> Option<bool>;
     }
 
-    fn push_fields(
-        &mut self,
-        fields: &mut Fields,
-        error_args: &ErrorArgs,
-        variant_error_args: &Option<ErrorArgs>,
-    ) -> Result<(), TokenStream> {
-        let output = self;
-
-        match fields {
-            Fields::Named(fields) => {
-                output.push_debug_title();
-
-                for field in &mut fields.named {
-                    if let Some(field_ident) = field.ident.clone() {
-                        output.push_field(
-                            field,
-                            &field_ident,
-                            &FieldIdentStyle::Prefixed,
-                            error_args,
-                            variant_error_args,
-                        )?;
-                    }
-                }
-            }
-            Fields::Unnamed(fields) => {
-                output.push_debug_title();
-
-                let ident_style = if fields.unnamed.len() > 1 {
-                    FieldIdentStyle::Prefixed
-                } else {
-                    FieldIdentStyle::Unprefixed
-                };
-
-                for (idx, field) in fields.unnamed.iter_mut().enumerate() {
-                    output.push_field(field, idx, &ident_style, error_args, variant_error_args)?;
-                }
-            }
-            Fields::Unit => (),
+    impl<'a, 'b, T: Eq + ?Sized> EqViaEq for &'b EqProbe<'a, T> {
+        fn justerror_eq(&self) -> Option<bool> {
+            Some(self.0 == self.1)
         }
+    }
 
-        Ok(())
+    pub trait EqFallback {
+        fn justerror_eq(&self) -> Option<bool>;
     }
 
-    fn push_field(
-        &mut self,
-        field: &mut Field,
-        ident: impl Display,
-        ident_style: &FieldIdentStyle,
-        error_args: &ErrorArgs,
-        variant_error_args: &Option<ErrorArgs>,
-    ) -> Result<(), TokenStream> {
-        let mut field_fmt_attr = None;
-
-        for (idx, attr) in field.attrs.iter().enumerate() {
-            if attr.path.is_ident(FMT_ATTR) {
-                field_fmt_attr = match attr.parse_args::<Fmt>() {
-                    Ok(fmt) => Some((idx, fmt)),
-                    Err(err) => return Err(err.into_compile_error()),
-                };
-            }
+    impl<'a, T: ?Sized> EqFallback for EqProbe<'a, T> {
+        fn justerror_eq(&self) -> Option<bool> {
+            None
         }
+    }
 
-        let (field_fmt_attr_idx, field_fmt) = match field_fmt_attr {
-            Some((idx, fmt)) => (Some(idx), Some(fmt)),
-            None => (None, None),
-        };
+    // Iterator returned by `sources()`, `core::iter::successors` is stable since Rust 1.34 only
+    pub struct Sources<'a>(pub Option<&'a (dyn Error + 'static)>);
 
-        if let Some(idx) = field_fmt_attr_idx {
-            field.attrs.remove(idx);
-        }
+    impl<'a> Iterator for Sources<'a> {
+        type Item = &'a (dyn Error + 'static);
 
-        let fmt = Fmt::derive(error_args, variant_error_args, &field_fmt);
+        fn next(&mut self) -> Option<Self::Item> {
+            let error = self.0?;
+            self.0 = error.source();
+            Some(error)
+        }
+    }
 
-        let buf = &mut self.0;
+    // Renders `context_map` pairs in the layout of the fields
+    #[cfg(feature = "std")]
+    pub struct Context<'a>(pub &'a crate::ContextMap, pub char, pub &'static str);
 
-        let ident = ident.to_string();
-        let fmt = fmt.to_string();
+    #[cfg(feature = "std")]
+    impl<'a> core::fmt::Display for Context<'a> {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            let Context(context, line_sep, field_sep) = self;
 
-        buf.push('\n');
+            for (key, value) in context.iter() {
+                write!(f, "{}{}{}{}", line_sep, key, field_sep, value)?;
+            }
 
-        if let FieldIdentStyle::Prefixed = ident_style {
-            buf.push_str(&ident);
-            buf.push_str(": ");
+            Ok(())
         }
+    }
 
-        buf.push('{');
-        buf.push_str(&ident);
-        buf.push_str(&fmt);
-        buf.push('}');
+    // Numbers the errors of `aggregate` variants, one per line
+    pub struct Numbered<T>(pub T);
 
-        Ok(())
+    impl<T> core::fmt::Display for Numbered<T>
+    where
+        T: Copy + IntoIterator,
+        T::Item: core::fmt::Display,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write_numbered(f, self.0, "\n")
+        }
     }
-}
 
-impl ToTokens for Output {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.0.to_tokens(tokens)
+    // Same list on a single line for the compact layout
+    pub struct NumberedInline<T>(pub T);
+
+    impl<T> core::fmt::Display for NumberedInline<T>
+    where
+        T: Copy + IntoIterator,
+        T::Item: core::fmt::Display,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write_numbered(f, self.0, " ")
+        }
     }
-}
 
-/// See [crate documentation](https://docs.rs/justerror)
-#[proc_macro_attribute]
-#[allow(non_snake_case)]
-pub fn Error(args: CompilerTokenStream, item: CompilerTokenStream) -> CompilerTokenStream {
-    let mut error = parse_macro_input!(item as DeriveInput);
-
-    let error_args = if !args.is_empty() {
-        parse_macro_input!(args as ErrorArgs)
-    } else {
-        ErrorArgs::default()
-    };
-
-    match &mut error.data {
-        Data::Enum(data) => {
-            for variant in &mut data.variants {
-                let mut variant_error_attr: Option<(usize, ErrorArgs)> = None;
-
-                for (idx, attr) in &mut variant.attrs.iter().enumerate() {
-                    if attr.path.is_ident(ERROR_ATTR) {
-                        let error_args = match attr.parse_args::<ErrorArgs>() {
-                            Ok(args) => args,
-                            Err(err) => return err.into_compile_error().into(),
-                        };
-                        variant_error_attr = Some((idx, error_args));
-                    }
-                }
+    fn write_numbered<T>(f: &mut core::fmt::Formatter, items: T, sep: &str) -> core::fmt::Result
+    where
+        T: IntoIterator,
+        T::Item: core::fmt::Display,
+    {
+        for (idx, item) in items.into_iter().enumerate() {
+            if idx > 0 {
+                f.write_str(sep)?;
+            }
+            write!(f, "{}. {}", idx + 1, item)?;
+        }
 
-                let (variant_error_attr_idx, variant_error_args) = match variant_error_attr {
-                    Some((idx, args)) => (Some(idx), Some(args)),
-                    None => (None, None),
-                };
+        Ok(())
+    }
 
-                if let Some(idx) = variant_error_attr_idx {
-                    variant.attrs.remove(idx);
-                }
+    // Indents all lines but the first of fields rendered by `nest` errors
+    pub struct Indent<T>(pub T);
 
-                let mut output = Output::new();
-
-                output.push_title(&error.ident, Some(&variant.ident));
-
-                match (&error_args.desc, &variant_error_args) {
-                    (
-                        Some(error_desc),
-                        Some(ErrorArgs {
-                            desc: Some(variant_desc),
-                            fmt: _,
-                        }),
-                    ) => {
-                        output.push_desc(Some(&error.ident), error_desc);
-                        output.push_desc(Some(&variant.ident), variant_desc);
-                    }
-                    (Some(error_desc), Some(ErrorArgs { desc: None, fmt: _ }) | None) => {
-                        output.push_desc(None, error_desc);
-                    }
-                    (
-                        None,
-                        Some(ErrorArgs {
-                            desc: Some(variant_desc),
-                            fmt: _,
-                        }),
-                    ) => {
-                        output.push_desc(None, variant_desc);
-                    }
-                    (None, Some(ErrorArgs { desc: None, fmt: _ }) | None) => (),
-                };
-
-                if let Err(err) =
-                    output.push_fields(&mut variant.fields, &error_args, &variant_error_args)
-                {
-                    return err.into();
-                }
+    impl<T: core::fmt::Display> core::fmt::Display for Indent<T> {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            use core::fmt::Write as _;
 
-                variant.attrs.push(parse_quote!(#[error(#output)]));
-            }
+            write!(IndentLines(f), "{}", self.0)
         }
-        Data::Struct(data) => {
-            let mut output = Output::new();
+    }
+
+    struct IndentLines<'a, 'b>(&'a mut core::fmt::Formatter<'b>);
 
-            output.push_title(&error.ident, None);
+    impl<'a, 'b> core::fmt::Write for IndentLines<'a, 'b> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let mut lines = s.split('\n');
 
-            if let Some(desc) = &error_args.desc {
-                output.push_desc(None, desc);
+            if let Some(line) = lines.next() {
+                self.0.write_str(line)?;
             }
 
-            if let Err(err) = output.push_fields(&mut data.fields, &error_args, &None) {
-                {
-                    return err.into();
-                }
+            for line in lines {
+                self.0.write_str("\n  ")?;
+                self.0.write_str(line)?;
             }
 
-            error.attrs.push(parse_quote!(#[error(#output)]));
-        }
-        Data::Union(_) => {
-            return SyntaxError::new_spanned(
-                error,
-                "Untagged unions are not supported by the Error macro.",
-            )
-            .to_compile_error()
-            .into()
+            Ok(())
         }
     }
 
-    quote! {
-      #[derive(thiserror::Error, Debug)]
-      #error
+    struct JsonEscape<'a, 'b>(&'a mut core::fmt::Formatter<'b>);
+
+    impl<'a, 'b> core::fmt::Write for JsonEscape<'a, 'b> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            for char in s.chars() {
+                match char {
+                    '"' => self.0.write_str("\\\"")?,
+                    '\\' => self.0.write_str("\\\\")?,
+                    '\n' => self.0.write_str("\\n")?,
+                    '\r' => self.0.write_str("\\r")?,
+                    '\t' => self.0.write_str("\\t")?,
+                    char if char.is_control() => write!(self.0, "\\u{:04x}", char as u32)?,
+                    char => self.0.write_char(char)?,
+                }
+            }
+
+            Ok(())
+        }
     }
-    .into()
 }
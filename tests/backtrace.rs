@@ -0,0 +1,84 @@
+#![cfg(feature = "backtrace")]
+#![feature(error_generic_member_access)]
+
+#[macro_use]
+extern crate justerror;
+
+use std::backtrace::Backtrace;
+
+use indoc::indoc;
+
+#[Error]
+enum EnumErrorWithBacktrace {
+    Io(#[backtrace] Backtrace, std::io::Error),
+    Captured(Backtrace),
+}
+
+fn io_error() -> EnumErrorWithBacktrace {
+    EnumErrorWithBacktrace::Io(
+        Backtrace::capture(),
+        std::io::Error::new(std::io::ErrorKind::Other, "Oh no"),
+    )
+}
+
+#[test]
+fn it_provides_backtrace_field() {
+    let error = io_error();
+    let backtrace = std::error::request_ref::<Backtrace>(&error);
+
+    assert!(backtrace.is_some());
+}
+
+#[test]
+fn it_excludes_backtrace_field_from_output() {
+    let actual = format!("{}", io_error());
+    let expected = indoc! {r#"
+        EnumErrorWithBacktrace::Io
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_only_backtrace_field() {
+    let actual = format!("{}", EnumErrorWithBacktrace::Captured(Backtrace::capture()));
+    let expected = "EnumErrorWithBacktrace::Captured";
+
+    assert_eq!(actual, expected);
+}
+
+// Types with sources implement `Error` without `thiserror`, `provide()` included
+#[Error]
+enum EnumErrorWithSourceAndBacktrace {
+    Io {
+        #[from]
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    Inner(
+        #[source]
+        #[backtrace]
+        EnumErrorWithBacktrace,
+    ),
+}
+
+#[test]
+fn it_provides_backtrace_field_next_to_source() {
+    let error = EnumErrorWithSourceAndBacktrace::from(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "Oh no",
+    ));
+    let backtrace = std::error::request_ref::<Backtrace>(&error);
+
+    assert!(backtrace.is_some());
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn it_provides_backtrace_of_source() {
+    let error = EnumErrorWithSourceAndBacktrace::Inner(io_error());
+    let backtrace = std::error::request_ref::<Backtrace>(&error);
+
+    assert!(backtrace.is_some());
+}
@@ -0,0 +1,12 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(aggregate)]
+    Many {
+        errors: Vec<String>,
+        count: usize,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![deny(deprecated)]
+
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(transparent)]
+    Io(std::io::Error),
+    #[error(transparent)]
+    Parse {
+        #[from]
+        inner: std::num::ParseIntError,
+    },
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+fn main() {
+    let _ = EnumError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Oh no"));
+}
@@ -0,0 +1,18 @@
+#![deny(deprecated)]
+
+use justerror::Error;
+
+#[Error(deprecated = "Use NewError instead")]
+struct OldError;
+
+#[Error]
+enum NewError {
+    Foo,
+    #[error(deprecated = "Use Foo instead")]
+    Bar,
+}
+
+fn main() {
+    let _ = OldError;
+    let _ = [NewError::Foo, NewError::Bar];
+}
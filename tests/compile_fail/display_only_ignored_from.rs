@@ -0,0 +1,18 @@
+#![deny(deprecated)]
+
+use justerror::Error;
+
+#[Error(impl_display_only)]
+enum DisplayOnlyError {
+    Io(#[from] std::io::Error),
+    Fmt {
+        #[source]
+        cause: std::fmt::Error,
+    },
+}
+
+fn main() {
+    let _ = DisplayOnlyError::Fmt {
+        cause: std::fmt::Error,
+    };
+}
@@ -0,0 +1,12 @@
+use justerror::Error;
+
+#[Error(impl_display_only)]
+struct DisplayOnlyError {
+    message: &'static str,
+}
+
+fn assert_error<E: std::error::Error>() {}
+
+fn main() {
+    assert_error::<DisplayOnlyError>();
+}
@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(desc = "First", fmt = debug, desc = "Second")]
+enum EnumError {
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// Duplicate codes are reported as `deprecated` warnings
+#![deny(deprecated)]
+
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(code = 1)]
+    Foo,
+    #[error(code = 1)]
+    Bar,
+}
+
+fn main() {}
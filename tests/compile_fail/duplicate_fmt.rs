@@ -0,0 +1,6 @@
+use justerror::Error;
+
+#[Error(fmt = debug, desc = "My error", fmt = display)]
+struct StructError(&'static str);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    Read(#[from] std::io::Error),
+    Fmt(#[from] std::fmt::Error),
+    Write(#[from] std::io::Error),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    Foo {
+        #[reason]
+        message: String,
+        #[reason]
+        details: String,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(desc = "Foo error")]
+    #[error(code = 1)]
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use justerror::Error;
+
+trait ErrorCode {
+    fn code(&self) -> u32;
+}
+
+#[Error(impl_error_code = "ErrorCode")]
+enum EnumError {
+    #[error(code = 100)]
+    Foo,
+    Bar,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(fmt = 42)]
+enum EnumError {
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use justerror::Error;
+
+#[Error]
+struct FillError {
+    #[fmt(fill = '*', width = 5)]
+    a: usize,
+}
+
+#[Error]
+struct AlignError {
+    #[fmt(align = top)]
+    a: usize,
+}
+
+#[Error]
+struct DuplicateError {
+    #[fmt(width = 5, width = 6)]
+    a: usize,
+}
+
+fn main() {}
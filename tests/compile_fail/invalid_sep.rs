@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(sep = "{}")]
+enum EnumError {
+    Foo { a: usize },
+}
+
+fn main() {}
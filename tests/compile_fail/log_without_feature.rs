@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(log_on_create)]
+enum EnumError {
+    Foo,
+}
+
+fn main() {}
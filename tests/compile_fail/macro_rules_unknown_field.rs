@@ -0,0 +1,14 @@
+use justerror::Error;
+
+macro_rules! define_error {
+    ($name:ident, $desc:literal) => {
+        #[Error(desc = $desc)]
+        enum $name {
+            Foo { value: usize },
+        }
+    };
+}
+
+define_error!(MacroRulesError, "Value {missing}");
+
+fn main() {}
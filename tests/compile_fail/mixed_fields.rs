@@ -0,0 +1,14 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    Foo { a: std::collections::HashMap<u8, u8>, usize },
+}
+
+#[Error]
+struct StructError {
+    pub a: fn(u8) -> Vec<u8>,
+    &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use justerror_no_thiserror::NonExhaustiveError;
+
+fn main() {
+    match NonExhaustiveError::Foo {
+        NonExhaustiveError::Foo => (),
+        NonExhaustiveError::Bar { .. } => (),
+    }
+}
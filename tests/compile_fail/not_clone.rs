@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error(clone)]
+enum EnumError {
+    Foo(String),
+    Bar(Box<dyn std::error::Error + Send + Sync>),
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(hash)]
+struct StructError {
+    ratio: f64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error(send_sync)]
+enum EnumError {
+    Foo,
+    Bar { a: &'static str, b: std::rc::Rc<str> },
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(raw = "Custom {0}", desc = "My error")]
+    Foo(usize),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use justerror::Error;
+
+#[Error(title = false)]
+enum EnumError {
+    Foo {
+        #[reason]
+        reason: String,
+    },
+}
+
+fn main() {}
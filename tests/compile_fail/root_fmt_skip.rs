@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(fmt = skip)]
+enum EnumError {
+    Foo(usize),
+}
+
+fn main() {}
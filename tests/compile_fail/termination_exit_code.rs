@@ -0,0 +1,13 @@
+use justerror::Error;
+
+#[Error(termination, exit_code = 256)]
+struct StructError;
+
+#[Error(termination)]
+enum EnumError {
+    Foo,
+    #[error(exit_code = 0)]
+    Bar,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(desc = "Foo error")]
+    #[error("foo failed: {0}")]
+    Foo(u8),
+}
+
+fn main() {}
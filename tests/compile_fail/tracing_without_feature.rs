@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error(tracing_event)]
+enum EnumError {
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error(transparent)]
+enum EnumError {
+    Foo(std::io::Error),
+    Bar(std::io::Error, usize),
+}
+
+fn main() {}
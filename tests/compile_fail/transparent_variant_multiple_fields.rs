@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(transparent)]
+    Foo(std::io::Error, usize),
+}
+
+fn main() {}
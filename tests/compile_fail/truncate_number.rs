@@ -0,0 +1,14 @@
+use justerror::Error;
+
+#[Error]
+struct StructError {
+    #[fmt(truncate(3))]
+    ratio: f64,
+}
+
+#[Error(fmt = truncate(10))]
+enum EnumError {
+    Foo { query: String, attempts: &'static u32 },
+}
+
+fn main() {}
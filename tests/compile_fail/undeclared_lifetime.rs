@@ -0,0 +1,8 @@
+use justerror::Error;
+
+#[Error]
+enum ParseError {
+    InvalidToken { text: &'a str },
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+union UnionError {
+    a: u32,
+    b: f32,
+}
+
+fn main() {}
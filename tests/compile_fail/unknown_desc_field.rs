@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(desc = "Failed to open {path}")]
+    Foo { file: String },
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[fmt("ratio: {}/{}")]
+    Foo(u8),
+}
+
+fn main() {}
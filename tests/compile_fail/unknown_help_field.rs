@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error(help = "Check the token {token}")]
+enum EnumError {
+    Foo { token: String },
+    Bar,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#![deny(deprecated)]
+
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(desc = "Foo error", typo = "something")]
+    Foo,
+    #[error(future(a, b))]
+    Bar,
+}
+
+fn main() {
+    let _ = [EnumError::Foo, EnumError::Bar];
+}
@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+struct TimeoutError {
+    #[default = 30]
+    timeout: u64,
+}
+
+fn main() {}
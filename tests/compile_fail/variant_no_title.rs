@@ -0,0 +1,9 @@
+use justerror::Error;
+
+#[Error]
+enum EnumError {
+    #[error(no_title)]
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,424 @@
+use std::error::Error as _;
+
+// Types are declared in both styles under the same names, so their messages can be compared as is
+mod attr {
+    use justerror::Error;
+
+    #[Error]
+    pub enum EnumError {
+        Foo,
+        Bar { a: &'static str, b: usize },
+        Baz(&'static str),
+        Qux(#[fmt(debug)] Vec<&'static str>, usize),
+    }
+
+    #[Error(desc = "My enum error", fmt = debug, code = 100, help = "Check the input")]
+    pub enum EnumErrorWithArgs {
+        /// Foo error
+        Foo,
+        #[error(desc = "Bar error", fmt = display, url = "https://example.com/")]
+        Bar {
+            a: &'static str,
+            #[fmt("05")]
+            b: usize,
+        },
+        #[error(desc = "Baz error: {0}", compact)]
+        Baz(&'static str),
+        Qux(#[skip] Vec<&'static str>, usize, &'static str),
+    }
+
+    #[Error(desc = "My struct error", header = "---")]
+    pub struct StructError {
+        pub a: &'static str,
+        #[fmt(debug)]
+        pub b: Vec<usize>,
+    }
+
+    #[Error]
+    pub struct UnitStructError;
+
+    #[Error(fmt = debug)]
+    pub enum FromError {
+        Io(#[from] std::io::Error),
+        Fetch {
+            url: &'static str,
+            #[source]
+            #[fmt(display)]
+            cause: std::fmt::Error,
+        },
+        Boxed {
+            source: Box<dyn std::error::Error + Send + Sync>,
+        },
+    }
+
+    #[Error(transparent)]
+    pub enum TransparentError {
+        Io(std::io::Error),
+        Fmt { inner: std::fmt::Error },
+    }
+
+    #[Error]
+    pub enum EmptyError {}
+
+    #[Error(fmt = json)]
+    pub enum JsonError {
+        Foo { a: &'static str, b: usize },
+        Bar(#[fmt(debug)] Vec<&'static str>),
+    }
+
+    #[Error]
+    pub enum RawError {
+        #[error(raw = "Failed to read {path:?}")]
+        Read { path: &'static str },
+        #[error(raw = "Failed to write {0} bytes")]
+        Write(usize),
+    }
+
+    #[Error(code = 1)]
+    pub enum CfgError {
+        Foo(#[from] std::fmt::Error),
+        #[cfg(test)]
+        Bar {
+            a: &'static str,
+        },
+        #[cfg(not(test))]
+        Baz(#[from] std::io::Error),
+    }
+
+    #[Error(nest)]
+    pub enum NestError {
+        #[error(desc = "Count: {1}")]
+        Foo(EnumError, usize),
+    }
+
+    #[Error]
+    pub struct LabelError {
+        #[label = "request timeout (ms)"]
+        pub req_timeout_ms: usize,
+    }
+
+    #[Error(constructor)]
+    pub struct TimeoutError {
+        #[default = 30]
+        pub timeout: u64,
+        pub message: String,
+    }
+}
+
+mod derive {
+    use justerror::derive::Error;
+
+    #[derive(Debug, Error)]
+    pub enum EnumError {
+        Foo,
+        Bar { a: &'static str, b: usize },
+        Baz(&'static str),
+        Qux(#[fmt(debug)] Vec<&'static str>, usize),
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(desc = "My enum error", fmt = debug, code = 100, help = "Check the input")]
+    pub enum EnumErrorWithArgs {
+        /// Foo error
+        Foo,
+        #[error(desc = "Bar error", fmt = display, url = "https://example.com/")]
+        Bar {
+            a: &'static str,
+            #[fmt("05")]
+            b: usize,
+        },
+        #[error(desc = "Baz error: {0}", compact)]
+        Baz(&'static str),
+        Qux(#[skip] Vec<&'static str>, usize, &'static str),
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(desc = "My struct error", header = "---")]
+    pub struct StructError {
+        pub a: &'static str,
+        #[fmt(debug)]
+        pub b: Vec<usize>,
+    }
+
+    #[derive(Debug, Error)]
+    pub struct UnitStructError;
+
+    #[derive(Debug, Error)]
+    #[justerror(fmt = debug)]
+    pub enum FromError {
+        Io(#[from] std::io::Error),
+        Fetch {
+            url: &'static str,
+            #[source]
+            #[fmt(display)]
+            cause: std::fmt::Error,
+        },
+        Boxed {
+            source: Box<dyn std::error::Error + Send + Sync>,
+        },
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(transparent)]
+    pub enum TransparentError {
+        Io(std::io::Error),
+        Fmt { inner: std::fmt::Error },
+    }
+
+    #[derive(Debug, Error)]
+    pub enum EmptyError {}
+
+    #[derive(Debug, Error)]
+    #[justerror(fmt = json)]
+    pub enum JsonError {
+        Foo { a: &'static str, b: usize },
+        Bar(#[fmt(debug)] Vec<&'static str>),
+    }
+
+    #[derive(Debug, Error)]
+    pub enum RawError {
+        #[error(raw = "Failed to read {path:?}")]
+        Read { path: &'static str },
+        #[error(raw = "Failed to write {0} bytes")]
+        Write(usize),
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(code = 1)]
+    pub enum CfgError {
+        Foo(#[from] std::fmt::Error),
+        #[cfg(test)]
+        Bar {
+            a: &'static str,
+        },
+        #[cfg(not(test))]
+        Baz(#[from] std::io::Error),
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(nest)]
+    pub enum NestError {
+        #[error(desc = "Count: {1}")]
+        Foo(EnumError, usize),
+    }
+
+    #[derive(Debug, Error)]
+    pub struct LabelError {
+        #[label = "request timeout (ms)"]
+        pub req_timeout_ms: usize,
+    }
+
+    #[derive(Debug, Error)]
+    #[justerror(constructor)]
+    pub struct TimeoutError {
+        #[default = 30]
+        pub timeout: u64,
+        pub message: String,
+    }
+}
+
+#[test]
+fn it_formats_enum_error_same_as_attribute() {
+    assert_eq!(
+        attr::EnumError::Foo.to_string(),
+        derive::EnumError::Foo.to_string()
+    );
+    assert_eq!(
+        attr::EnumError::Bar { a: "Hey!", b: 42 }.to_string(),
+        derive::EnumError::Bar { a: "Hey!", b: 42 }.to_string()
+    );
+    assert_eq!(
+        attr::EnumError::Baz("Hey!").to_string(),
+        derive::EnumError::Baz("Hey!").to_string()
+    );
+    assert_eq!(
+        attr::EnumError::Qux(vec!["Hey!"], 42).to_string(),
+        derive::EnumError::Qux(vec!["Hey!"], 42).to_string()
+    );
+}
+
+#[test]
+fn it_formats_enum_error_with_args_same_as_attribute() {
+    assert_eq!(
+        attr::EnumErrorWithArgs::Foo.to_string(),
+        derive::EnumErrorWithArgs::Foo.to_string()
+    );
+    assert_eq!(
+        attr::EnumErrorWithArgs::Bar { a: "Hey!", b: 42 }.to_string(),
+        derive::EnumErrorWithArgs::Bar { a: "Hey!", b: 42 }.to_string()
+    );
+    assert_eq!(
+        attr::EnumErrorWithArgs::Baz("Hey!").to_string(),
+        derive::EnumErrorWithArgs::Baz("Hey!").to_string()
+    );
+    assert_eq!(
+        attr::EnumErrorWithArgs::Qux(vec!["Hey!"], 42, "Ho!").to_string(),
+        derive::EnumErrorWithArgs::Qux(vec!["Hey!"], 42, "Ho!").to_string()
+    );
+    assert_eq!(
+        attr::EnumErrorWithArgs::Foo.error_code(),
+        derive::EnumErrorWithArgs::Foo.error_code()
+    );
+}
+
+#[test]
+fn it_formats_struct_error_same_as_attribute() {
+    assert_eq!(
+        attr::StructError {
+            a: "Hey!",
+            b: vec![42]
+        }
+        .to_string(),
+        derive::StructError {
+            a: "Hey!",
+            b: vec![42]
+        }
+        .to_string()
+    );
+    assert_eq!(
+        attr::UnitStructError.to_string(),
+        derive::UnitStructError.to_string()
+    );
+}
+
+#[test]
+fn it_converts_and_exposes_sources_same_as_attribute() {
+    let io = || std::io::Error::other("oops");
+
+    let attr_err = attr::FromError::from(io());
+    let derive_err = derive::FromError::from(io());
+    assert_eq!(attr_err.to_string(), derive_err.to_string());
+    assert_eq!(
+        attr_err.source().map(ToString::to_string),
+        derive_err.source().map(ToString::to_string)
+    );
+
+    let attr_err = attr::FromError::Fetch {
+        url: "https://example.com",
+        cause: std::fmt::Error,
+    };
+    let derive_err = derive::FromError::Fetch {
+        url: "https://example.com",
+        cause: std::fmt::Error,
+    };
+    assert_eq!(attr_err.to_string(), derive_err.to_string());
+    assert!(derive_err.source().is_some());
+
+    let attr_err = attr::FromError::Boxed {
+        source: "Boxed!".into(),
+    };
+    let derive_err = derive::FromError::Boxed {
+        source: "Boxed!".into(),
+    };
+    assert_eq!(attr_err.to_string(), derive_err.to_string());
+    assert_eq!(
+        derive_err.source().map(ToString::to_string),
+        Some("Boxed!".to_string())
+    );
+}
+
+#[test]
+fn it_formats_transparent_error_same_as_attribute() {
+    let io = || std::io::Error::other("oops");
+
+    assert_eq!(
+        attr::TransparentError::Io(io()).to_string(),
+        derive::TransparentError::Io(io()).to_string()
+    );
+    assert_eq!(
+        attr::TransparentError::Fmt {
+            inner: std::fmt::Error
+        }
+        .to_string(),
+        derive::TransparentError::Fmt {
+            inner: std::fmt::Error
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn it_implements_error_for_empty_enum() {
+    fn assert_error<E: std::error::Error>() {}
+
+    assert_error::<attr::EmptyError>();
+    assert_error::<derive::EmptyError>();
+}
+
+#[test]
+fn it_formats_json_error_same_as_attribute() {
+    assert_eq!(
+        attr::JsonError::Foo {
+            a: "\"Hey!\"",
+            b: 42
+        }
+        .to_string(),
+        derive::JsonError::Foo {
+            a: "\"Hey!\"",
+            b: 42
+        }
+        .to_string()
+    );
+    assert_eq!(
+        attr::JsonError::Bar(vec!["Hey!"]).to_string(),
+        derive::JsonError::Bar(vec!["Hey!"]).to_string()
+    );
+}
+
+#[test]
+fn it_formats_nest_error_same_as_attribute() {
+    assert_eq!(
+        attr::NestError::Foo(attr::EnumError::Bar { a: "Hey!", b: 42 }, 42).to_string(),
+        derive::NestError::Foo(derive::EnumError::Bar { a: "Hey!", b: 42 }, 42).to_string()
+    );
+}
+
+#[test]
+fn it_formats_cfg_error_same_as_attribute() {
+    assert_eq!(
+        attr::CfgError::Bar { a: "Hey!" }.to_string(),
+        derive::CfgError::Bar { a: "Hey!" }.to_string()
+    );
+    assert_eq!(
+        attr::CfgError::from(std::fmt::Error).to_string(),
+        derive::CfgError::from(std::fmt::Error).to_string()
+    );
+}
+
+#[test]
+fn it_formats_raw_error_same_as_attribute() {
+    assert_eq!(
+        attr::RawError::Write(42).to_string(),
+        derive::RawError::Write(42).to_string()
+    );
+    assert_eq!(
+        attr::RawError::Read { path: "a.txt" }.to_string(),
+        derive::RawError::Read { path: "a.txt" }.to_string()
+    );
+}
+
+#[test]
+fn it_formats_label_error_same_as_attribute() {
+    assert_eq!(
+        attr::LabelError {
+            req_timeout_ms: 5000
+        }
+        .to_string(),
+        derive::LabelError {
+            req_timeout_ms: 5000
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn it_fills_default_fields_same_as_attribute() {
+    let error = derive::TimeoutError::new("Oh no".to_string());
+
+    assert_eq!(error.timeout, 30);
+    assert_eq!(error.message, "Oh no");
+    assert_eq!(
+        attr::TimeoutError::new("Oh no".to_string()).to_string(),
+        error.to_string()
+    );
+}
@@ -0,0 +1,71 @@
+//! Checks that errors are logged on creation with `log_on_create`.
+
+use justerror::Error;
+
+#[Error(desc = "My enum error", log_on_create)]
+pub enum EnumError {
+    Foo { a: &'static str },
+    Bar(u8),
+    Baz,
+}
+
+#[Error(log_on_create)]
+pub struct StructError {
+    pub a: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{Level, Log, Metadata, Record};
+
+    use super::*;
+
+    static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+    struct Recorder;
+
+    impl Log for Recorder {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    // The logger is global, so the records are checked in a single test
+    #[test]
+    fn it_logs_errors_on_creation() {
+        log::set_logger(&Recorder).unwrap();
+        log::set_max_level(log::LevelFilter::Error);
+
+        let _ = EnumError::new_foo("A");
+        let _ = EnumError::new_bar(1);
+        let _ = EnumError::new_baz();
+        let _ = StructError::new(42);
+
+        assert_eq!(
+            *RECORDS.lock().unwrap(),
+            vec![
+                (
+                    Level::Error,
+                    "EnumError::Foo\nMy enum error\n=== ↴\na: A".to_string()
+                ),
+                (
+                    Level::Error,
+                    "EnumError::Bar\nMy enum error\n=== ↴\n1".to_string()
+                ),
+                (Level::Error, "EnumError::Baz\nMy enum error".to_string()),
+                (Level::Error, "StructError\n=== ↴\na: 42".to_string()),
+            ]
+        );
+    }
+}
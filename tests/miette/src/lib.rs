@@ -0,0 +1,105 @@
+//! Checks that `miette::Diagnostic` is derived with the `miette` feature.
+
+use justerror::Error;
+use miette::SourceSpan;
+
+#[Error(
+    desc = "Failed to parse config",
+    code = 100,
+    url = "https://docs.example.com/"
+)]
+pub enum ConfigError {
+    #[error(help = "Check the syntax near `{token}`")]
+    Syntax {
+        token: String,
+        #[source_code]
+        src: String,
+        #[label = "unexpected token"]
+        span: SourceSpan,
+    },
+    Missing,
+}
+
+#[Error(desc = "My struct error")]
+pub struct StructError {
+    pub a: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::{Diagnostic, NarratableReportHandler};
+
+    use super::*;
+
+    fn syntax_error() -> ConfigError {
+        ConfigError::Syntax {
+            token: "=".to_string(),
+            src: "a = = 1".to_string(),
+            span: (4, 1).into(),
+        }
+    }
+
+    #[test]
+    fn it_forwards_arguments_to_diagnostic() {
+        let error = syntax_error();
+
+        assert_eq!(
+            error.code().map(|code| code.to_string()),
+            Some("E100".to_string())
+        );
+        assert_eq!(
+            error.help().map(|help| help.to_string()),
+            Some("Check the syntax near `=`".to_string())
+        );
+        assert_eq!(
+            error.url().map(|url| url.to_string()),
+            Some("https://docs.example.com/E100".to_string())
+        );
+
+        let labels: Vec<_> = error.labels().into_iter().flatten().collect();
+
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].label(), Some("unexpected token"));
+        assert_eq!(labels[0].offset(), 4);
+    }
+
+    #[test]
+    fn it_falls_back_to_desc_as_help() {
+        assert_eq!(
+            ConfigError::Missing.help().map(|help| help.to_string()),
+            Some("Failed to parse config".to_string())
+        );
+        assert_eq!(
+            StructError { a: 1 }.help().map(|help| help.to_string()),
+            Some("My struct error".to_string())
+        );
+    }
+
+    #[test]
+    fn it_converts_into_report() {
+        fn load() -> Result<(), miette::Report> {
+            Err(ConfigError::Missing)?;
+            Ok(())
+        }
+
+        let report = load().unwrap_err();
+
+        assert_eq!(report.to_string(), "[E101] ConfigError::Missing\nFailed to parse config\nsee: https://docs.example.com/E101");
+        assert!(report.downcast_ref::<ConfigError>().is_some());
+    }
+
+    #[test]
+    fn it_renders_report() {
+        let mut actual = String::new();
+
+        NarratableReportHandler::new()
+            .render_report(&mut actual, &syntax_error())
+            .unwrap();
+
+        assert!(actual.contains("ConfigError::Syntax"));
+        assert!(actual.contains("Check the syntax near `=`"));
+        assert!(actual.contains("unexpected token"));
+        // Spans are labels of the source code, not fields of the message
+        assert!(!actual.contains("span:"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Checks that generated code compiles without `std`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use justerror::Error;
+
+#[Error(desc = "My enum error", code = 100, predicates)]
+pub enum EnumError {
+    Foo,
+    Bar {
+        a: String,
+        b: usize,
+    },
+    Qux(#[fmt(debug)] Vec<u8>),
+    Fmt(#[from] core::fmt::Error),
+    Boxed {
+        #[source]
+        inner: Box<dyn core::error::Error + Send + Sync>,
+    },
+}
+
+#[Error(transparent)]
+pub struct TransparentError(EnumError);
+
+#[derive(Debug, justerror::derive::Error)]
+#[justerror(desc = "My struct error")]
+pub struct StructError {
+    pub a: String,
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use alloc::{format, string::ToString};
+    use core::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn it_formats_error_without_std() {
+        let actual = format!(
+            "{}",
+            EnumError::Bar {
+                a: "A".to_string(),
+                b: 42
+            }
+        );
+        let expected = "[E101] EnumError::Bar\nMy enum error\n=== ↴\na: A\nb: 42";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn it_exposes_source_without_std() {
+        let err = TransparentError(EnumError::from(core::fmt::Error));
+
+        assert_eq!(
+            err.to_string(),
+            EnumError::Fmt(core::fmt::Error).to_string()
+        );
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,34 @@
+//! Checks that generated code compiles without `thiserror` in the dependencies.
+
+use justerror::Error;
+
+#[Error(desc = "My enum error")]
+pub enum EnumError {
+    Foo,
+    Bar { a: &'static str, b: usize },
+    Io(#[from] std::io::Error),
+}
+
+#[Error]
+pub enum OtherEnumError {
+    Foo(#[source] std::io::Error),
+}
+
+#[Error(transparent)]
+pub struct TransparentError(std::io::Error);
+
+// Used by trybuild tests of the root crate to check `#[non_exhaustive]` from another crate
+#[Error]
+#[non_exhaustive]
+pub enum NonExhaustiveError {
+    Foo,
+    Bar { a: &'static str },
+}
+
+#[test]
+fn it_formats_error_without_thiserror_dependency() {
+    let actual = format!("{}", EnumError::Bar { a: "A", b: 42 });
+    let expected = "EnumError::Bar\nMy enum error\n=== ↴\na: A\nb: 42";
+
+    assert_eq!(actual, expected);
+}
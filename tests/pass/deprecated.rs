@@ -0,0 +1,32 @@
+#![deny(deprecated)]
+
+use justerror::Error;
+
+// Generated code must not trigger warnings for deprecated types and variants
+#[Error(
+    deprecated = "Use NewError instead",
+    code = 100,
+    exit_code = 2,
+    predicates,
+    accessors,
+    variant_name,
+    result_alias = "OldResult"
+)]
+enum OldError {
+    Foo(#[from] std::io::Error),
+    Bar { a: &'static str },
+}
+
+#[Error(predicates, accessors, variant_name)]
+enum NewError {
+    Foo,
+    #[error(deprecated = "Use Foo instead")]
+    Bar(&'static str),
+}
+
+#[allow(deprecated)]
+fn main() {
+    let _ = [OldError::Bar { a: "Hey!" }];
+    let _: OldResult<()> = Ok(());
+    let _ = [NewError::Foo, NewError::Bar("Hey!")];
+}
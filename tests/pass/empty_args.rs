@@ -0,0 +1,10 @@
+use justerror::Error;
+
+#[Error()]
+enum EnumError {
+    Foo,
+}
+
+fn main() {
+    assert_eq!(EnumError::Foo.to_string(), "EnumError::Foo");
+}
@@ -0,0 +1,16 @@
+use justerror::Error;
+
+#[Error(no_debug)]
+enum EnumError {
+    Foo,
+}
+
+impl std::fmt::Debug for EnumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EnumError")
+    }
+}
+
+fn main() {
+    let _: &dyn std::error::Error = &EnumError::Foo;
+}
@@ -0,0 +1,17 @@
+#![deny(dead_code)]
+
+use justerror::Error;
+
+#[Error(code = 100, exit_code = 2, predicates, accessors, variant_name, result_alias = "EnumResult")]
+enum EnumError {
+    Foo,
+    Bar { a: &'static str },
+}
+
+#[Error(code = 200, exit_code = 3, category = "io", variant_name, result_alias = "StructResult")]
+struct StructError;
+
+fn main() {
+    let _ = [EnumError::Foo, EnumError::Bar { a: "Hey!" }];
+    let _ = StructError;
+}
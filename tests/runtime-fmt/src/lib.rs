@@ -0,0 +1,71 @@
+//! Checks that the layout is switched at runtime with `runtime_fmt`.
+
+use justerror::Error;
+
+#[Error(desc = "My enum error", code = 100)]
+pub enum EnumError {
+    Foo,
+    Bar {
+        a: &'static str,
+        #[fmt(debug)]
+        b: Vec<u8>,
+    },
+    #[error(compact = false)]
+    Verbose {
+        a: &'static str,
+    },
+}
+
+#[Error(compact)]
+pub struct CompactError {
+    pub a: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // The switch is global, so the layouts are checked in a single test
+    #[test]
+    fn it_switches_layout_at_runtime() {
+        let error = EnumError::Bar { a: "A", b: vec![1] };
+
+        assert!(!justerror::config::is_compact());
+
+        let expected = indoc! {r#"
+            [E101] EnumError::Bar
+            My enum error
+            === ↴
+            a: A
+            b: [
+                1,
+            ]"#};
+
+        assert_eq!(error.to_string(), expected);
+
+        justerror::config::set_compact(true);
+
+        assert!(justerror::config::is_compact());
+        assert_eq!(
+            error.to_string(),
+            "[E101] EnumError::Bar My enum error a=A b=[1]"
+        );
+        assert_eq!(
+            EnumError::Foo.to_string(),
+            "[E100] EnumError::Foo My enum error"
+        );
+
+        // Explicit `compact` is not affected by the switch
+        assert_eq!(
+            EnumError::Verbose { a: "A" }.to_string(),
+            "[E102] EnumError::Verbose\nMy enum error\n=== ↴\na: A"
+        );
+
+        justerror::config::set_compact(false);
+
+        assert_eq!(error.to_string(), expected);
+        assert_eq!(CompactError { a: "A" }.to_string(), "CompactError a=A");
+    }
+}
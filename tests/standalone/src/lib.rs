@@ -0,0 +1,155 @@
+//! Checks that generated code compiles without `thiserror`.
+
+use justerror::Error;
+
+#[Error(desc = "My enum error", code = 100)]
+pub enum EnumError {
+    Foo,
+    Bar {
+        a: &'static str,
+        b: usize,
+    },
+    Qux(#[fmt(debug)] Vec<u8>),
+    Io(#[from] std::io::Error),
+    Boxed {
+        #[source]
+        inner: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+#[Error(transparent)]
+pub struct TransparentError(EnumError);
+
+#[Error(desc = "My struct error", fmt = debug)]
+pub struct StructError<'a> {
+    pub a: &'a str,
+    pub source: std::fmt::Error,
+}
+
+#[Error(context_map)]
+pub struct ContextError {
+    pub path: &'static str,
+}
+
+#[Error]
+pub enum QuotaError {
+    #[fmt("used: {}/{} MB")]
+    Unnamed(u32, u32),
+}
+
+#[Error]
+pub enum FallibleError<E: std::error::Error> {
+    Outer { source: E },
+    Local,
+}
+
+#[Error]
+pub enum BatchError {
+    #[error(aggregate)]
+    Many { errors: Vec<&'static str> },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn it_formats_error_without_thiserror() {
+        let actual = format!("{}", EnumError::Bar { a: "A", b: 42 });
+        let expected = indoc! {r#"
+            [E101] EnumError::Bar
+            My enum error
+            === ↴
+            a: A
+            b: 42"#};
+
+        assert_eq!(actual, expected);
+
+        let actual = format!("{}", EnumError::Qux(vec![1]));
+        let expected = indoc! {r#"
+            [E102] EnumError::Qux
+            My enum error
+            === ↴
+            [
+                1,
+            ]"#};
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn it_formats_fields_with_variant_format_without_thiserror() {
+        assert_eq!(
+            QuotaError::Unnamed(5, 10).to_string(),
+            "QuotaError::Unnamed\nused: 5/10 MB"
+        );
+    }
+
+    #[test]
+    fn it_exposes_source_without_thiserror() {
+        let io = std::io::Error::other("oops");
+        let err = TransparentError(EnumError::from(io));
+
+        assert_eq!(
+            err.source().map(ToString::to_string),
+            Some("oops".to_string())
+        );
+
+        let err = EnumError::Boxed {
+            inner: "Boxed!".into(),
+        };
+
+        assert_eq!(
+            err.source().map(ToString::to_string),
+            Some("Boxed!".to_string())
+        );
+
+        let err = StructError {
+            a: "A",
+            source: std::fmt::Error,
+        };
+
+        assert!(err.source().is_some());
+        assert_eq!(
+            err.to_string(),
+            "StructError\nMy struct error\n=== ↴\na: \"A\"\nsource: Error"
+        );
+
+        let err: FallibleError<std::fmt::Error> = FallibleError::Outer {
+            source: std::fmt::Error,
+        };
+
+        assert!(err.source().is_some());
+        assert!(FallibleError::<std::fmt::Error>::Local.source().is_none());
+    }
+
+    #[test]
+    fn it_formats_context_map_without_thiserror() {
+        let err = ContextError {
+            path: "a.txt",
+            context: Default::default(),
+        }
+        .with_context("attempt", 1);
+
+        assert_eq!(
+            err.to_string(),
+            "ContextError\n=== ↴\npath: a.txt\nattempt: 1"
+        );
+    }
+
+    #[test]
+    fn it_numbers_errors_of_aggregate_variant_without_thiserror() {
+        let err = BatchError::Many {
+            errors: vec!["first error", "second error"],
+        };
+
+        assert_eq!(
+            err.to_string(),
+            "BatchError::Many\n=== ↴\n1. first error\n2. second error"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Checks that `strum_display` leaves `Display` to `strum::Display`.
+
+use justerror::Error;
+
+#[Error(strum_display)]
+#[derive(strum::Display)]
+pub enum EnumError {
+    #[strum(to_string = "{path} not found")]
+    NotFound {
+        path: &'static str,
+    },
+    #[strum(to_string = "Access denied")]
+    Denied,
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn it_renders_strum_display() {
+        let err = EnumError::NotFound { path: "a.txt" };
+
+        assert_eq!(format!("{}", err), "a.txt not found");
+        assert_eq!(err.to_string(), "a.txt not found");
+        assert_eq!(EnumError::Denied.to_string(), "Access denied");
+    }
+
+    #[test]
+    fn it_implements_error_with_strum_display() {
+        let err: EnumError = std::io::Error::other("Oh no").into();
+
+        assert_eq!(err.to_string(), "Io");
+        assert!(err.source().is_some());
+    }
+}
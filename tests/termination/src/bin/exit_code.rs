@@ -0,0 +1,21 @@
+//! Returns an error with `termination` and `exit_code` from `main`.
+
+use justerror::Error;
+
+#[Error(termination, exit_code = 2)]
+enum CliError {
+    #[error(exit_code = 3)]
+    NotFound {
+        path: &'static str,
+    },
+    Other,
+}
+
+fn main() -> CliError {
+    match std::env::args().nth(1).as_deref() {
+        Some("other") => CliError::Other,
+        _ => CliError::NotFound {
+            path: "config.toml",
+        },
+    }
+}
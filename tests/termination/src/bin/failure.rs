@@ -0,0 +1,14 @@
+//! Returns an error with `termination` from `main`.
+
+use justerror::Error;
+
+#[Error(termination)]
+struct CliError {
+    path: &'static str,
+}
+
+fn main() -> CliError {
+    CliError {
+        path: "config.toml",
+    }
+}
@@ -0,0 +1,48 @@
+use std::process::{Command, Output};
+
+use indoc::indoc;
+
+fn run(bin: &str, args: &[&str]) -> Output {
+    Command::new(bin).args(args).output().unwrap()
+}
+
+#[test]
+fn it_reports_message_and_fails() {
+    let output = run(env!("CARGO_BIN_EXE_failure"), &[]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        indoc! {"
+            CliError
+            === ↴
+            path: config.toml
+        "}
+    );
+}
+
+#[test]
+fn it_exits_with_variant_exit_code() {
+    let output = run(env!("CARGO_BIN_EXE_exit_code"), &[]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        indoc! {"
+            CliError::NotFound
+            === ↴
+            path: config.toml
+        "}
+    );
+}
+
+#[test]
+fn it_exits_with_root_exit_code() {
+    let output = run(env!("CARGO_BIN_EXE_exit_code"), &["other"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "CliError::Other\n"
+    );
+}
@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate justerror;
 
+use std::error::Error as _;
+
 use indoc::indoc;
 
 #[Error]
@@ -26,6 +28,414 @@ enum EnumErrorWithArgs {
     Qux(Vec<&'static str>, usize),
 }
 
+#[Error(desc = "My enum error with sources", fmt = debug)]
+enum EnumErrorWithFrom {
+    #[error(fmt = display)]
+    Io(#[from] std::io::Error),
+    Parse(#[from] std::num::ParseIntError),
+}
+
+#[Error(fmt = debug)]
+enum EnumErrorWithSource {
+    Fetch {
+        #[source]
+        #[fmt(display)]
+        inner: std::io::Error,
+        url: &'static str,
+    },
+}
+
+#[Error(transparent)]
+enum TransparentEnumError {
+    Io(std::io::Error),
+    Parse {
+        #[from]
+        inner: std::num::ParseIntError,
+    },
+}
+
+#[Error]
+enum EnumErrorWithSkippedFields {
+    Named {
+        a: &'static str,
+        #[skip]
+        #[fmt(debug)]
+        b: Vec<u8>,
+    },
+    SingleUnnamed(#[skip] Vec<u8>, &'static str),
+    MultipleUnnamed(#[skip] Vec<u8>, &'static str, #[skip] Vec<u8>, usize),
+    AllSkipped(#[skip] Vec<u8>),
+}
+
+// Doesn't implement `Display`
+#[derive(Debug)]
+struct Secret;
+
+#[Error]
+enum EnumErrorWithSensitiveFields {
+    Login {
+        user: &'static str,
+        #[sensitive]
+        password: &'static str,
+    },
+    Token(#[sensitive] &'static str),
+    Key(#[sensitive] Secret),
+}
+
+#[Error]
+struct StructErrorWithTruncatedField {
+    #[fmt(truncate(5))]
+    query: String,
+}
+
+#[Error(fmt = truncate(5))]
+struct StructErrorWithTruncatedFields {
+    query: String,
+    #[fmt(display)]
+    attempts: u32,
+}
+
+#[Error(header = "--- Fields ---")]
+enum EnumErrorWithCustomHeader {
+    Foo { a: &'static str },
+    Bar(usize),
+}
+
+#[Error(header = "--- {x} ---")]
+struct StructErrorWithBracesInHeader {
+    x: usize,
+}
+
+#[Error(header = "")]
+struct StructErrorWithoutHeader {
+    a: &'static str,
+    b: usize,
+}
+
+#[Error(desc = "My compact error", compact)]
+enum CompactEnumError {
+    Foo,
+    Bar {
+        a: &'static str,
+        #[fmt(debug)]
+        b: Vec<usize>,
+    },
+    Baz(&'static str),
+    #[error(compact = false)]
+    Qux(&'static str, usize),
+}
+
+#[Error]
+enum PartiallyCompactEnumError {
+    #[error(compact)]
+    Foo {
+        a: &'static str,
+        b: usize,
+    },
+    Bar {
+        a: &'static str,
+        b: usize,
+    },
+}
+
+#[Error(compact)]
+struct CompactStructError {
+    a: &'static str,
+    b: usize,
+}
+
+#[Error(code = 1040)]
+enum EnumErrorWithCodes {
+    Foo,
+    #[error(code = 2001)]
+    Bar {
+        a: &'static str,
+    },
+    Baz(&'static str),
+}
+
+#[Error]
+enum EnumErrorWithVariantCode {
+    Foo,
+    #[error(code = 2001, desc = "Bar error")]
+    Bar,
+}
+
+#[Error(code = 1042, compact)]
+struct StructErrorWithCode {
+    a: &'static str,
+}
+
+#[Error(help = "Check that the config file exists")]
+enum EnumErrorWithHelp {
+    Foo,
+    #[error(desc = "Bar error", help = "Check the permissions")]
+    Bar {
+        a: &'static str,
+    },
+    #[error(desc = "Baz error")]
+    Baz,
+}
+
+#[Error]
+enum EnumErrorWithVariantHelp {
+    #[error(help = "Try again later")]
+    Foo,
+    Bar,
+}
+
+#[Error(desc = "My struct error", help = "Check the input")]
+struct StructErrorWithHelp;
+
+#[Error]
+enum EnumErrorWithFieldsInHelp {
+    #[error(help = "Check `{token}` in {type}::{variant}, e.g. wrap it in {{}}")]
+    Syntax { token: &'static str },
+}
+
+#[Error(url = "https://docs.example.com/errors/", code = 1042)]
+enum EnumErrorWithUrl {
+    Foo {
+        a: &'static str,
+    },
+    #[error(url = "https://docs.example.com/bar")]
+    Bar,
+}
+
+#[Error(url = "https://docs.example.com/{version}/errors")]
+struct StructErrorWithTemplatedUrl;
+
+#[Error(fmt = json, url = "https://docs.example.com/{version}/errors")]
+struct JsonErrorWithTemplatedUrl;
+
+#[Error(url = "https://docs.example.com/struct-error")]
+struct StructErrorWithUrl;
+
+#[Error]
+struct GenericStructError<T>(T);
+
+#[Error(fmt = debug)]
+enum GenericEnumError<T, U> {
+    Foo(T),
+    Bar { a: U },
+}
+
+#[Error]
+struct MixedBoundsGenericError<T, U> {
+    #[fmt(debug)]
+    a: T,
+    b: U,
+}
+
+#[Error]
+enum GenericErrorWithSource<T> {
+    Parse {
+        value: T,
+        #[source]
+        source: std::num::ParseIntError,
+    },
+}
+
+#[Error]
+enum FallibleError<E: std::error::Error> {
+    Outer { source: E },
+    Local,
+}
+
+#[Error(code = 1)]
+enum LifetimeEnumError<'src> {
+    Token(&'src str),
+}
+
+#[Error(fmt = display)]
+struct LifetimeStructError<'src> {
+    span: &'src str,
+    msg: String,
+}
+
+#[Error(fmt = debug, result_alias = "BufferResult")]
+struct BufferError<const N: usize> {
+    buf: [u8; N],
+}
+
+#[Error(code = 10, fmt = debug, accessors)]
+enum ConstGenericEnumError<'a, const N: usize> {
+    Overflow { buf: [u8; N] },
+    Invalid(&'a str),
+}
+
+#[Error(predicates)]
+enum EnumErrorWithPredicates {
+    Timeout,
+    NotFound { path: &'static str },
+    HTTPStatus(u16),
+}
+
+#[Error(accessors)]
+enum EnumErrorWithAccessors {
+    Foo,
+    Bar { a: &'static str, b: usize },
+    Baz(&'static str),
+    Qux(&'static str, usize),
+}
+
+#[Error(desc = "My non-exhaustive error")]
+#[non_exhaustive]
+enum NonExhaustiveEnumError {
+    Foo { a: &'static str },
+}
+
+#[Error(prefix = "[myapp]")]
+enum EnumErrorWithPrefix {
+    Foo,
+    #[error(prefix = "[db]")]
+    Bar {
+        a: &'static str,
+    },
+    Baz(&'static str),
+}
+
+#[Error]
+enum EnumErrorWithVariantPrefix {
+    #[error(prefix = "[{net}]")]
+    Foo,
+    Bar,
+}
+
+#[Error(prefix = "[myapp]", code = 7)]
+struct StructErrorWithPrefix;
+
+#[Error(desc = "{type} failed")]
+enum EnumErrorWithDescPlaceholders {
+    #[error(desc = "{variant} happened in {type}")]
+    Foo { a: &'static str },
+    #[error(desc = "{{variant}} is literal")]
+    Bar,
+}
+
+#[Error(desc = "{type} ({variant}) failed")]
+struct StructErrorWithDescPlaceholders;
+
+#[Error]
+enum EnumErrorWithDescFields {
+    #[error(desc = "Failed to open {path} ({code:?})")]
+    Foo { path: &'static str, code: u8 },
+    #[error(desc = "Failed at {0}, {{0}} is literal")]
+    Bar(u8),
+}
+
+#[Error(title = false)]
+enum EnumErrorWithoutTitle {
+    #[error(desc = "Foo error")]
+    Foo {
+        a: &'static str,
+    },
+    #[error(desc = "Bar error")]
+    Bar,
+    Baz {
+        a: &'static str,
+    },
+    Qux,
+    #[error(title = true, desc = "Quux error")]
+    Quux,
+}
+
+#[Error(no_title)]
+enum EnumErrorWithNoTitle {
+    #[error(desc = "Foo error")]
+    Foo {
+        a: &'static str,
+    },
+    Bar,
+}
+
+#[Error]
+enum EnumErrorWithVariantWithoutTitle {
+    #[error(title = false, desc = "Foo error")]
+    Foo,
+    Bar,
+}
+
+#[Error(title = false, desc = "My struct error")]
+struct StructErrorWithoutTitle {
+    a: u8,
+}
+
+#[Error(sep = " => ")]
+enum EnumErrorWithSep {
+    Foo {
+        a: &'static str,
+        b: usize,
+    },
+    #[error(sep = "=")]
+    Bar(&'static str, usize),
+}
+
+#[Error(variant_name)]
+enum EnumErrorWithVariantName {
+    Foo,
+    Bar {
+        a: &'static str,
+    },
+    Baz(&'static str),
+    HttpRequest(u16, usize),
+    #[error(desc = "Timed out")]
+    Timeout,
+}
+
+#[Error(variant_name)]
+struct StructErrorWithVariantName;
+
+#[Error(derives(Clone, PartialEq))]
+enum EnumErrorWithDerives {
+    Foo,
+    Bar { a: &'static str },
+}
+
+#[Error(derives(Clone, Debug, thiserror::Error))]
+struct StructErrorWithDuplicateDerives(&'static str);
+
+#[Error]
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind")]
+enum EnumErrorWithSerde {
+    Foo,
+    Bar { a: String, b: usize },
+}
+
+#[Error(no_debug)]
+struct StructErrorWithCustomDebug(&'static str);
+
+impl std::fmt::Debug for StructErrorWithCustomDebug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Custom({})", self.0)
+    }
+}
+
+#[Error(desc = "My documented error")]
+enum EnumErrorWithDocComments {
+    /// The connection timed out
+    Timeout,
+    /// The resource was not found.
+    ///
+    /// Check the `{path}` field.
+    NotFound { path: &'static str },
+    /// Documentation only
+    #[error(desc = "Explicit description")]
+    Explicit,
+}
+
+#[Error(result_alias = "AliasedResult")]
+enum EnumErrorWithResultAlias {
+    Foo,
+}
+
+#[Error(result_alias = "GenericAliasedResult")]
+struct GenericStructErrorWithResultAlias<'a, T> {
+    a: &'a str,
+    b: T,
+}
+
 #[Error(desc = "My struct error")]
 struct MultipleNamedFieldsStructError {
     a: &'static str,
@@ -36,6 +446,48 @@ struct MultipleNamedFieldsStructError {
 #[Error]
 struct SingleUnnamedFieldStructError(&'static str);
 
+#[Error]
+struct UnitStructError;
+
+#[Error(desc = "Resource was not found")]
+struct UnitStructErrorWithDesc;
+
+#[Error(fmt = debug)]
+struct UnitStructErrorWithFmt;
+
+#[Error]
+struct SingleDebugFieldStructError(#[fmt(debug)] Vec<u8>);
+
+#[Error]
+struct SingleAlignedFieldStructError(#[fmt(">10")] &'static str);
+
+#[Error(fmt = debug)]
+struct StructErrorWithFieldFormats {
+    #[fmt(display)]
+    a: &'static str,
+    #[fmt(">10")]
+    b: usize,
+    c: &'static str,
+}
+
+#[Error]
+struct StructErrorWithDebugField {
+    #[fmt(debug)]
+    a: Vec<&'static str>,
+}
+
+#[Error]
+struct Point(f64, f64);
+
+#[Error]
+struct MixedFormatTupleStructError(&'static str, #[fmt(debug)] Vec<u8>, #[fmt(">5")] usize);
+
+#[Error(fmt = debug)]
+struct DebugTupleStructError(&'static str, #[fmt(display)] usize);
+
+#[Error(transparent)]
+struct TransparentStructError(#[from] std::io::Error);
+
 #[test]
 fn it_formats_enum_error_without_fields() {
     let actual = format!("{}", EnumError::Foo);
@@ -159,3 +611,3079 @@ fn it_formats_single_unnamed_field_struct_error() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn it_formats_unit_struct_error() {
+    let actual = format!("{}", UnitStructError);
+    let expected = "UnitStructError";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_unit_struct_error_with_desc() {
+    let actual = format!("{}", UnitStructErrorWithDesc);
+    let expected = indoc! {r#"
+        UnitStructErrorWithDesc
+        Resource was not found"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_unit_struct_error_with_fmt_without_header() {
+    let actual = format!("{}", UnitStructErrorWithFmt);
+    let expected = "UnitStructErrorWithFmt";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_single_unnamed_debug_field_struct_error() {
+    let actual = format!("{}", SingleDebugFieldStructError(vec![1, 2, 3]));
+    let expected = indoc! {r#"
+        SingleDebugFieldStructError
+        === ↴
+        [
+            1,
+            2,
+            3,
+        ]"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_single_unnamed_custom_format_field_struct_error() {
+    let actual = format!("{}", SingleAlignedFieldStructError("Oh no"));
+    let expected = indoc! {r#"
+        SingleAlignedFieldStructError
+        === ↴
+             Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_field_formats() {
+    let actual = format!(
+        "{}",
+        StructErrorWithFieldFormats {
+            a: "A",
+            b: 42,
+            c: "C"
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithFieldFormats
+        === ↴
+        a: A
+        b:         42
+        c: "C""#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_debug_field() {
+    let actual = format!("{}", StructErrorWithDebugField { a: vec!["A"] });
+    let expected = indoc! {r#"
+        StructErrorWithDebugField
+        === ↴
+        a: [
+            "A",
+        ]"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_tuple_struct_error_with_two_fields() {
+    let actual = format!("{}", Point(1.0, 2.5));
+    let expected = indoc! {r#"
+        Point
+        === ↴
+        0: 1
+        1: 2.5"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_tuple_struct_error_with_mixed_field_formats() {
+    let actual = format!("{}", MixedFormatTupleStructError("A", vec![1], 42));
+    let expected = indoc! {r#"
+        MixedFormatTupleStructError
+        === ↴
+        0: A
+        1: [
+            1,
+        ]
+        2:    42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_tuple_struct_error_using_root_format() {
+    let actual = format!("{}", DebugTupleStructError("A", 42));
+    let expected = indoc! {r#"
+        DebugTupleStructError
+        === ↴
+        0: "A"
+        1: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_converts_from_source_error_into_enum_error() {
+    let error: EnumErrorWithFrom = std::io::Error::other("Oh no").into();
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        EnumErrorWithFrom::Io
+        My enum error with sources
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_from_field_using_root_format() {
+    let error: EnumErrorWithFrom = "x".parse::<usize>().unwrap_err().into();
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        EnumErrorWithFrom::Parse
+        My enum error with sources
+        === ↴
+        ParseIntError {
+            kind: InvalidDigit,
+        }"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_exposes_source_field_as_error_source() {
+    let error = EnumErrorWithSource::Fetch {
+        inner: std::io::Error::other("Oh no"),
+        url: "https://example.com",
+    };
+    let source = error
+        .source()
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .expect("source is an io::Error");
+
+    assert_eq!(source.to_string(), "Oh no");
+}
+
+#[test]
+fn it_formats_source_field_using_field_format() {
+    let error = EnumErrorWithSource::Fetch {
+        inner: std::io::Error::other("Oh no"),
+        url: "https://example.com",
+    };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        EnumErrorWithSource::Fetch
+        === ↴
+        inner: Oh no
+        url: "https://example.com""#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_transparent_enum_error_with_unnamed_field() {
+    let error = TransparentEnumError::Io(std::io::Error::other("Oh no"));
+    let actual = format!("{}", error);
+    let expected = "Oh no";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_transparent_enum_error_with_named_field() {
+    let error: TransparentEnumError = "x".parse::<usize>().unwrap_err().into();
+    let actual = format!("{}", error);
+    let expected = "invalid digit found in string";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_transparent_struct_error() {
+    let error: TransparentStructError = std::io::Error::other("Oh no").into();
+    let actual = format!("{}", error);
+    let expected = "Oh no";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_skips_named_field() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithSkippedFields::Named { a: "A", b: vec![1] }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithSkippedFields::Named
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_skips_unnamed_field_leaving_single_unprefixed_field() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithSkippedFields::SingleUnnamed(vec![1], "Oh no")
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithSkippedFields::SingleUnnamed
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_skips_unnamed_fields_keeping_indices() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithSkippedFields::MultipleUnnamed(vec![1], "Oh no", vec![2], 42)
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithSkippedFields::MultipleUnnamed
+        === ↴
+        1: Oh no
+        3: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_omits_header_when_all_fields_are_skipped() {
+    let actual = format!("{}", EnumErrorWithSkippedFields::AllSkipped(vec![1]));
+    let expected = "EnumErrorWithSkippedFields::AllSkipped";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_redacts_sensitive_named_field() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithSensitiveFields::Login {
+            user: "admin",
+            password: "hunter2",
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithSensitiveFields::Login
+        === ↴
+        user: admin
+        password: [REDACTED]"#};
+
+    assert_eq!(actual, expected);
+    assert!(!actual.contains("hunter2"));
+}
+
+#[test]
+fn it_redacts_sensitive_unnamed_field() {
+    let actual = format!("{}", EnumErrorWithSensitiveFields::Token("s3cr3t"));
+    let expected = indoc! {r#"
+        EnumErrorWithSensitiveFields::Token
+        === ↴
+        [REDACTED]"#};
+
+    assert_eq!(actual, expected);
+    assert!(!actual.contains("s3cr3t"));
+}
+
+#[test]
+fn it_does_not_require_display_for_sensitive_field() {
+    let actual = format!("{}", EnumErrorWithSensitiveFields::Key(Secret));
+    let expected = indoc! {r#"
+        EnumErrorWithSensitiveFields::Key
+        === ↴
+        [REDACTED]"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_truncates_long_field() {
+    let actual = format!(
+        "{}",
+        StructErrorWithTruncatedField {
+            query: "SELECT * FROM users".to_string()
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithTruncatedField
+        === ↴
+        query: SELEC"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_truncates_fields_except_numbers() {
+    let actual = format!(
+        "{}",
+        StructErrorWithTruncatedFields {
+            query: "SELECT * FROM users".to_string(),
+            attempts: 123456,
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithTruncatedFields
+        === ↴
+        query: SELEC
+        attempts: 123456"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_does_not_truncate_short_field() {
+    let actual = format!(
+        "{}",
+        StructErrorWithTruncatedField {
+            query: "SEL".to_string()
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithTruncatedField
+        === ↴
+        query: SEL"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_custom_header() {
+    let actual = format!("{}", EnumErrorWithCustomHeader::Foo { a: "A" });
+    let expected = indoc! {r#"
+        EnumErrorWithCustomHeader::Foo
+        --- Fields ---
+        a: A"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithCustomHeader::Bar(42));
+    let expected = indoc! {r#"
+        EnumErrorWithCustomHeader::Bar
+        --- Fields ---
+        42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_keeps_braces_of_custom_header() {
+    let actual = format!("{}", StructErrorWithBracesInHeader { x: 1 });
+    let expected = indoc! {r#"
+        StructErrorWithBracesInHeader
+        --- {x} ---
+        x: 1"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_empty_header() {
+    let actual = format!("{}", StructErrorWithoutHeader { a: "A", b: 42 });
+    let expected = indoc! {r#"
+        StructErrorWithoutHeader
+        a: A
+        b: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_compact_enum_error_without_fields() {
+    let actual = format!("{}", CompactEnumError::Foo);
+    let expected = "CompactEnumError::Foo My compact error";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_compact_enum_error_with_named_fields() {
+    let actual = format!(
+        "{}",
+        CompactEnumError::Bar {
+            a: "A",
+            b: vec![1, 2]
+        }
+    );
+    let expected = "CompactEnumError::Bar My compact error a=A b=[1, 2]";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_compact_enum_error_with_single_unnamed_field() {
+    let actual = format!("{}", CompactEnumError::Baz("Oh no"));
+    let expected = "CompactEnumError::Baz My compact error Oh no";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_level_compact_override() {
+    let actual = format!("{}", CompactEnumError::Qux("Oh no", 42));
+    let expected = indoc! {r#"
+        CompactEnumError::Qux
+        My compact error
+        === ↴
+        0: Oh no
+        1: 42"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", PartiallyCompactEnumError::Foo { a: "A", b: 42 });
+    let expected = "PartiallyCompactEnumError::Foo a=A b=42";
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", PartiallyCompactEnumError::Bar { a: "A", b: 42 });
+    let expected = indoc! {r#"
+        PartiallyCompactEnumError::Bar
+        === ↴
+        a: A
+        b: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_compact_struct_error() {
+    let actual = format!("{}", CompactStructError { a: "A", b: 42 });
+    let expected = "CompactStructError a=A b=42";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_codes() {
+    let actual = format!("{}", EnumErrorWithCodes::Foo);
+    let expected = "[E1040] EnumErrorWithCodes::Foo";
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithCodes::Bar { a: "A" });
+    let expected = indoc! {r#"
+        [E2001] EnumErrorWithCodes::Bar
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithCodes::Baz("Oh no"));
+    let expected = indoc! {r#"
+        [E1042] EnumErrorWithCodes::Baz
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_returns_enum_error_codes() {
+    assert_eq!(EnumErrorWithCodes::Foo.error_code(), Some(1040));
+    assert_eq!(EnumErrorWithCodes::Bar { a: "A" }.error_code(), Some(2001));
+    assert_eq!(EnumErrorWithCodes::Baz("Oh no").error_code(), Some(1042));
+    assert_eq!(EnumErrorWithVariantCode::Foo.error_code(), None);
+    assert_eq!(EnumErrorWithVariantCode::Bar.error_code(), Some(2001));
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_code() {
+    let actual = format!("{}", EnumErrorWithVariantCode::Bar);
+    let expected = indoc! {r#"
+        [E2001] EnumErrorWithVariantCode::Bar
+        Bar error"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_code() {
+    let error = StructErrorWithCode { a: "A" };
+
+    assert_eq!(format!("{}", error), "[E1042] StructErrorWithCode a=A");
+    assert_eq!(error.error_code(), Some(1042));
+}
+
+#[test]
+fn it_formats_enum_error_with_root_help() {
+    let actual = format!("{}", EnumErrorWithHelp::Foo);
+    let expected = indoc! {r#"
+        EnumErrorWithHelp::Foo
+        hint: Check that the config file exists"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithHelp::Baz);
+    let expected = indoc! {r#"
+        EnumErrorWithHelp::Baz
+        Baz error
+        hint: Check that the config file exists"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_help_overriding_root_help() {
+    let actual = format!("{}", EnumErrorWithHelp::Bar { a: "A" });
+    let expected = indoc! {r#"
+        EnumErrorWithHelp::Bar
+        Bar error
+        hint: Check the permissions
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_help() {
+    let actual = format!("{}", EnumErrorWithVariantHelp::Foo);
+    let expected = indoc! {r#"
+        EnumErrorWithVariantHelp::Foo
+        hint: Try again later"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithVariantHelp::Bar);
+    let expected = "EnumErrorWithVariantHelp::Bar";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_help() {
+    let actual = format!("{}", StructErrorWithHelp);
+    let expected = indoc! {r#"
+        StructErrorWithHelp
+        My struct error
+        hint: Check the input"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_fields_in_help() {
+    let actual = format!("{}", EnumErrorWithFieldsInHelp::Syntax { token: "=" });
+    let expected = indoc! {r#"
+        EnumErrorWithFieldsInHelp::Syntax
+        hint: Check `=` in EnumErrorWithFieldsInHelp::Syntax, e.g. wrap it in {}
+        === ↴
+        token: ="#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_url_appending_code() {
+    let actual = format!("{}", EnumErrorWithUrl::Foo { a: "A" });
+    let expected = indoc! {r#"
+        [E1042] EnumErrorWithUrl::Foo
+        === ↴
+        a: A
+        see: https://docs.example.com/errors/E1042"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_url_overriding_root_url() {
+    let actual = format!("{}", EnumErrorWithUrl::Bar);
+    let expected = indoc! {r#"
+        [E1043] EnumErrorWithUrl::Bar
+        see: https://docs.example.com/bar"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_url() {
+    let actual = format!("{}", StructErrorWithUrl);
+    let expected = indoc! {r#"
+        StructErrorWithUrl
+        see: https://docs.example.com/struct-error"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_keeps_braces_of_templated_url() {
+    let actual = format!("{}", StructErrorWithTemplatedUrl);
+    let expected = indoc! {r#"
+        StructErrorWithTemplatedUrl
+        see: https://docs.example.com/{version}/errors"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", JsonErrorWithTemplatedUrl);
+    let json: serde_json::Value = serde_json::from_str(&actual).unwrap();
+
+    assert_eq!(json["url"], "https://docs.example.com/{version}/errors");
+}
+
+fn assert_error<E: std::error::Error>(_: &E) {}
+
+#[test]
+fn it_formats_generic_struct_error() {
+    let error = GenericStructError(42);
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        GenericStructError
+        === ↴
+        42"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_generic_enum_error_with_debug_only_params() {
+    let error = GenericEnumError::<Vec<u8>, Vec<u8>>::Foo(vec![1]);
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        GenericEnumError::Foo
+        === ↴
+        [
+            1,
+        ]"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", GenericEnumError::<(), _>::Bar { a: "A" });
+    let expected = indoc! {r#"
+        GenericEnumError::Bar
+        === ↴
+        a: "A""#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_generic_error_with_mixed_bounds() {
+    let error = MixedBoundsGenericError { a: vec![1], b: "B" };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        MixedBoundsGenericError
+        === ↴
+        a: [
+            1,
+        ]
+        b: B"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_infers_bounds_of_generic_error_with_source() {
+    let source = "x".parse::<u8>().unwrap_err();
+    let error = GenericErrorWithSource::Parse { value: 42, source };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        GenericErrorWithSource::Parse
+        === ↴
+        value: 42
+        source: invalid digit found in string"#};
+
+    assert_eq!(actual, expected);
+    assert!(error.source().is_some());
+}
+
+#[test]
+fn it_formats_generic_enum_error_with_error_bound() {
+    let error: FallibleError<std::io::Error> = FallibleError::Outer {
+        source: std::io::Error::other("x"),
+    };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        FallibleError::Outer
+        === ↴
+        source: x"#};
+
+    assert_error(&error);
+    assert!(error.source().is_some());
+    assert_eq!(actual, expected);
+
+    let error: FallibleError<std::io::Error> = FallibleError::Local;
+
+    assert!(error.source().is_none());
+    assert_eq!(format!("{}", error), "FallibleError::Local");
+}
+
+#[test]
+fn it_formats_enum_error_with_lifetime_in_unnamed_field() {
+    let source = String::from("let x");
+    let error = LifetimeEnumError::Token(&source[4..]);
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        [E1] LifetimeEnumError::Token
+        === ↴
+        x"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+    assert_eq!(error.error_code(), Some(1));
+}
+
+#[test]
+fn it_formats_struct_error_with_lifetime_in_named_field() {
+    let source = String::from("let x");
+    let error = LifetimeStructError {
+        span: &source[..3],
+        msg: "Unexpected keyword".to_string(),
+    };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        LifetimeStructError
+        === ↴
+        span: let
+        msg: Unexpected keyword"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_struct_error_with_const_generic() {
+    fn fill() -> BufferResult<(), 3> {
+        Err(BufferError { buf: [1, 2, 3] })
+    }
+
+    let error = fill().unwrap_err();
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        BufferError
+        === ↴
+        buf: [
+            1,
+            2,
+            3,
+        ]"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_const_generic() {
+    let error: ConstGenericEnumError<2> = ConstGenericEnumError::Overflow { buf: [1, 2] };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        [E10] ConstGenericEnumError::Overflow
+        === ↴
+        buf: [
+            1,
+            2,
+        ]"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+    assert_eq!(error.as_overflow(), Some(&[1, 2]));
+    assert_eq!(
+        ConstGenericEnumError::<2>::Invalid("x").error_code(),
+        Some(11)
+    );
+}
+
+#[test]
+fn it_generates_enum_error_predicates() {
+    let timeout = EnumErrorWithPredicates::Timeout;
+    let not_found = EnumErrorWithPredicates::NotFound { path: "/" };
+    let http_status = EnumErrorWithPredicates::HTTPStatus(500);
+
+    assert!(timeout.is_timeout());
+    assert!(!timeout.is_not_found());
+    assert!(!timeout.is_http_status());
+
+    assert!(!not_found.is_timeout());
+    assert!(not_found.is_not_found());
+    assert!(!not_found.is_http_status());
+
+    assert!(!http_status.is_timeout());
+    assert!(!http_status.is_not_found());
+    assert!(http_status.is_http_status());
+}
+
+#[test]
+fn it_generates_enum_error_accessors() {
+    let foo = EnumErrorWithAccessors::Foo;
+    let bar = EnumErrorWithAccessors::Bar { a: "A", b: 42 };
+    let baz = EnumErrorWithAccessors::Baz("Oh no");
+    let qux = EnumErrorWithAccessors::Qux("Oh no", 42);
+
+    assert_eq!(foo.as_foo(), Some(()));
+    assert_eq!(bar.as_bar(), Some((&"A", &42)));
+    assert_eq!(baz.as_baz(), Some(&"Oh no"));
+    assert_eq!(qux.as_qux(), Some((&"Oh no", &42)));
+
+    assert_eq!(bar.as_foo(), None);
+    assert_eq!(foo.as_bar(), None);
+    assert_eq!(qux.as_baz(), None);
+    assert_eq!(baz.as_qux(), None);
+}
+
+#[test]
+fn it_formats_non_exhaustive_enum_error() {
+    let actual = format!("{}", NonExhaustiveEnumError::Foo { a: "A" });
+    let expected = indoc! {r#"
+        NonExhaustiveEnumError::Foo
+        My non-exhaustive error
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_root_prefix() {
+    assert_eq!(
+        format!("{}", EnumErrorWithPrefix::Foo),
+        "[myapp] EnumErrorWithPrefix::Foo"
+    );
+    assert_eq!(
+        format!("{}", EnumErrorWithPrefix::Baz("Oh no")),
+        indoc! {r#"
+            [myapp] EnumErrorWithPrefix::Baz
+            === ↴
+            Oh no"#}
+    );
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_prefix_overriding_root_prefix() {
+    let actual = format!("{}", EnumErrorWithPrefix::Bar { a: "A" });
+    let expected = indoc! {r#"
+        [db] EnumErrorWithPrefix::Bar
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_prefix() {
+    assert_eq!(
+        format!("{}", EnumErrorWithVariantPrefix::Foo),
+        "[{net}] EnumErrorWithVariantPrefix::Foo"
+    );
+    assert_eq!(
+        format!("{}", EnumErrorWithVariantPrefix::Bar),
+        "EnumErrorWithVariantPrefix::Bar"
+    );
+}
+
+#[test]
+fn it_formats_struct_error_with_prefix() {
+    assert_eq!(
+        format!("{}", StructErrorWithPrefix),
+        "[E7] [myapp] StructErrorWithPrefix"
+    );
+}
+
+#[test]
+fn it_expands_desc_placeholders() {
+    let actual = format!("{}", EnumErrorWithDescPlaceholders::Foo { a: "A" });
+    let expected = indoc! {r#"
+        EnumErrorWithDescPlaceholders::Foo
+        EnumErrorWithDescPlaceholders: EnumErrorWithDescPlaceholders failed
+        Foo: Foo happened in EnumErrorWithDescPlaceholders
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_does_not_expand_escaped_desc_placeholders() {
+    let actual = format!("{}", EnumErrorWithDescPlaceholders::Bar);
+    let expected = indoc! {r#"
+        EnumErrorWithDescPlaceholders::Bar
+        EnumErrorWithDescPlaceholders: EnumErrorWithDescPlaceholders failed
+        Bar: {variant} is literal"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_desc_with_field_references() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithDescFields::Foo {
+            path: "/tmp",
+            code: 2
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithDescFields::Foo
+        Failed to open /tmp (2)
+        === ↴
+        path: /tmp
+        code: 2"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_does_not_expand_escaped_desc_field_references() {
+    let actual = format!("{}", EnumErrorWithDescFields::Bar(7));
+    let expected = indoc! {r#"
+        EnumErrorWithDescFields::Bar
+        Failed at 7, {0} is literal
+        === ↴
+        7"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_expands_desc_placeholders_in_struct_error() {
+    let actual = format!("{}", StructErrorWithDescPlaceholders);
+    let expected = indoc! {r#"
+        StructErrorWithDescPlaceholders
+        StructErrorWithDescPlaceholders (StructErrorWithDescPlaceholders) failed"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_without_title_with_desc_and_fields() {
+    let actual = format!("{}", EnumErrorWithoutTitle::Foo { a: "A" });
+    let expected = indoc! {r#"
+        Foo error
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_without_title_with_desc() {
+    assert_eq!(format!("{}", EnumErrorWithoutTitle::Bar), "Bar error");
+}
+
+#[test]
+fn it_formats_enum_error_without_title_with_fields() {
+    let actual = format!("{}", EnumErrorWithoutTitle::Baz { a: "A" });
+    let expected = indoc! {r#"
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_without_title_as_empty_string() {
+    assert_eq!(format!("{}", EnumErrorWithoutTitle::Qux), "");
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_title_overriding_root_title() {
+    let actual = format!("{}", EnumErrorWithoutTitle::Quux);
+    let expected = indoc! {r#"
+        EnumErrorWithoutTitle::Quux
+        Quux error"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_no_title_same_as_title_false() {
+    assert_eq!(
+        format!("{}", EnumErrorWithNoTitle::Foo { a: "A" }),
+        format!("{}", EnumErrorWithoutTitle::Foo { a: "A" })
+    );
+    assert_eq!(format!("{}", EnumErrorWithNoTitle::Bar), "");
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_without_title() {
+    assert_eq!(
+        format!("{}", EnumErrorWithVariantWithoutTitle::Foo),
+        "Foo error"
+    );
+    assert_eq!(
+        format!("{}", EnumErrorWithVariantWithoutTitle::Bar),
+        "EnumErrorWithVariantWithoutTitle::Bar"
+    );
+}
+
+#[test]
+fn it_formats_struct_error_without_title() {
+    let actual = format!("{}", StructErrorWithoutTitle { a: 42 });
+    let expected = indoc! {r#"
+        My struct error
+        === ↴
+        a: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_custom_sep() {
+    let actual = format!("{}", EnumErrorWithSep::Foo { a: "A", b: 42 });
+    let expected = indoc! {r#"
+        EnumErrorWithSep::Foo
+        === ↴
+        a => A
+        b => 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_enum_error_with_variant_sep_overriding_root_sep() {
+    let actual = format!("{}", EnumErrorWithSep::Bar("A", 42));
+    let expected = indoc! {r#"
+        EnumErrorWithSep::Bar
+        === ↴
+        0=A
+        1=42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_generates_enum_error_variant_name() {
+    assert_eq!(EnumErrorWithVariantName::Foo.variant_name(), "Foo");
+    assert_eq!(
+        EnumErrorWithVariantName::Bar { a: "A" }.variant_name(),
+        "Bar"
+    );
+    assert_eq!(EnumErrorWithVariantName::Baz("A").variant_name(), "Baz");
+    assert_eq!(
+        EnumErrorWithVariantName::HttpRequest(500, 42).variant_name(),
+        "HttpRequest"
+    );
+    assert_eq!(EnumErrorWithVariantName::Timeout.variant_name(), "Timeout");
+}
+
+#[test]
+fn it_generates_struct_error_variant_name() {
+    assert_eq!(
+        StructErrorWithVariantName.variant_name(),
+        "StructErrorWithVariantName"
+    );
+}
+
+#[test]
+fn it_derives_additional_traits() {
+    let error = EnumErrorWithDerives::Bar { a: "A" };
+
+    assert_eq!(error.clone(), error);
+    assert!(error != EnumErrorWithDerives::Foo);
+}
+
+#[test]
+fn it_does_not_duplicate_default_derives() {
+    let error = StructErrorWithDuplicateDerives("Oh no").clone();
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        StructErrorWithDuplicateDerives
+        === ↴
+        Oh no"#};
+
+    assert_error(&error);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_keeps_derives_of_the_type() {
+    let error = EnumErrorWithSerde::Bar {
+        a: "Hey!".to_string(),
+        b: 42,
+    };
+    let json = serde_json::to_string(&error).unwrap();
+    let actual = format!(
+        "{}",
+        serde_json::from_str::<EnumErrorWithSerde>(&json).unwrap()
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithSerde::Bar
+        === ↴
+        a: Hey!
+        b: 42"#};
+
+    assert_eq!(json, r#"{"kind":"Bar","a":"Hey!","b":42}"#);
+    assert_eq!(
+        serde_json::from_str::<EnumErrorWithSerde>(&json).unwrap(),
+        error
+    );
+    assert_eq!(actual, expected);
+    assert_error(&EnumErrorWithSerde::Foo);
+}
+
+#[test]
+fn it_uses_custom_debug_with_no_debug() {
+    let error = StructErrorWithCustomDebug("Oh no");
+
+    assert_error(&error);
+    assert_eq!(format!("{:?}", error), "Custom(Oh no)");
+}
+
+#[test]
+fn it_uses_single_line_doc_comment_as_variant_desc() {
+    let actual = format!("{}", EnumErrorWithDocComments::Timeout);
+    let expected = indoc! {r#"
+        EnumErrorWithDocComments::Timeout
+        EnumErrorWithDocComments: My documented error
+        Timeout: The connection timed out"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_uses_multi_line_doc_comment_as_variant_desc() {
+    let actual = format!("{}", EnumErrorWithDocComments::NotFound { path: "/" });
+    let expected = indoc! {r#"
+        EnumErrorWithDocComments::NotFound
+        EnumErrorWithDocComments: My documented error
+        NotFound: The resource was not found.
+
+        Check the `{path}` field.
+        === ↴
+        path: /"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_prefers_explicit_desc_over_doc_comment() {
+    let actual = format!("{}", EnumErrorWithDocComments::Explicit);
+    let expected = indoc! {r#"
+        EnumErrorWithDocComments::Explicit
+        EnumErrorWithDocComments: My documented error
+        Explicit: Explicit description"#};
+
+    assert_eq!(actual, expected);
+}
+
+fn fail_with_result_alias() -> AliasedResult<usize> {
+    Err(EnumErrorWithResultAlias::Foo)
+}
+
+fn fail_with_generic_result_alias(a: &str) -> GenericAliasedResult<'_, (), usize> {
+    Err(GenericStructErrorWithResultAlias { a, b: 42 })
+}
+
+#[test]
+fn it_generates_result_alias() {
+    let actual = format!("{}", fail_with_result_alias().unwrap_err());
+    let expected = "EnumErrorWithResultAlias::Foo";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_generates_result_alias_with_generics() {
+    let actual = format!("{}", fail_with_generic_result_alias("A").unwrap_err());
+    let expected = indoc! {r#"
+        GenericStructErrorWithResultAlias
+        === ↴
+        a: A
+        b: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(fmt = json, code = 7, desc = "My json error", help = "Retry later")]
+enum EnumErrorWithJson {
+    Foo,
+    #[error(desc = "Bar error", url = "https://example.com/")]
+    Bar {
+        a: &'static str,
+        b: usize,
+        #[fmt(debug)]
+        c: Vec<&'static str>,
+        #[sensitive]
+        d: &'static str,
+    },
+    Baz(&'static str, bool),
+}
+
+#[Error(fmt = json)]
+struct StructErrorWithJson {
+    a: &'static str,
+    #[skip]
+    b: usize,
+}
+
+#[test]
+fn it_formats_enum_error_as_json() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithJson::Bar {
+            a: "Say \"hi\"\n\\",
+            b: 42,
+            c: vec!["Hey!"],
+            d: "Secret",
+        }
+    );
+    let json: serde_json::Value = serde_json::from_str(&actual).unwrap();
+
+    assert_eq!(json["type"], "EnumErrorWithJson");
+    assert_eq!(json["variant"], "Bar");
+    assert_eq!(json["code"], 8);
+    assert_eq!(json["desc"], "My json error: Bar error");
+    assert_eq!(json["help"], "Retry later");
+    assert_eq!(json["url"], "https://example.com/E8");
+    assert_eq!(json["fields"]["a"], "Say \"hi\"\n\\");
+    assert_eq!(json["fields"]["b"], 42);
+    assert_eq!(json["fields"]["c"], "[\"Hey!\"]");
+    assert_eq!(json["fields"]["d"], "[REDACTED]");
+}
+
+#[test]
+fn it_formats_unit_and_tuple_variants_as_json() {
+    let actual = format!("{}", EnumErrorWithJson::Foo);
+    let expected = r#"{"type":"EnumErrorWithJson","variant":"Foo","code":7,"desc":"My json error","help":"Retry later"}"#;
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithJson::Baz("Hey!", true));
+    let json: serde_json::Value = serde_json::from_str(&actual).unwrap();
+
+    assert_eq!(json["fields"]["0"], "Hey!");
+    assert_eq!(json["fields"]["1"], true);
+}
+
+#[test]
+fn it_formats_struct_error_as_json() {
+    let actual = format!("{}", StructErrorWithJson { a: "Hey!", b: 42 });
+    let expected = r#"{"type":"StructErrorWithJson","fields":{"a":"Hey!"}}"#;
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(nest)]
+enum OuterErrorWithNest {
+    Foo(MiddleErrorWithNest),
+    Bar { a: MiddleErrorWithNest, b: usize },
+}
+
+#[Error(nest)]
+enum MiddleErrorWithNest {
+    Baz(InnerErrorWithNest),
+}
+
+#[Error]
+enum InnerErrorWithNest {
+    Qux { a: &'static str, b: usize },
+}
+
+#[test]
+fn it_indents_nested_errors() {
+    let actual = format!(
+        "{}",
+        OuterErrorWithNest::Foo(MiddleErrorWithNest::Baz(InnerErrorWithNest::Qux {
+            a: "Hey!",
+            b: 42
+        }))
+    );
+    let expected = indoc! {r#"
+        OuterErrorWithNest::Foo
+        === ↴
+        MiddleErrorWithNest::Baz
+          === ↴
+          InnerErrorWithNest::Qux
+            === ↴
+            a: Hey!
+            b: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_indents_nested_errors_in_named_fields() {
+    let actual = format!(
+        "{}",
+        OuterErrorWithNest::Bar {
+            a: MiddleErrorWithNest::Baz(InnerErrorWithNest::Qux { a: "Hey!", b: 42 }),
+            b: 42
+        }
+    );
+    let expected = indoc! {r#"
+        OuterErrorWithNest::Bar
+        === ↴
+        a: MiddleErrorWithNest::Baz
+          === ↴
+          InnerErrorWithNest::Qux
+            === ↴
+            a: Hey!
+            b: 42
+        b: 42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(exit_code = 70)]
+enum EnumErrorWithExitCodes {
+    Foo,
+    #[error(exit_code = 78)]
+    Bar {
+        a: &'static str,
+    },
+    Baz(&'static str),
+    #[error(exit_code = 0)]
+    Qux,
+    #[error(desc = "Quux error")]
+    Quux,
+}
+
+#[Error]
+enum EnumErrorWithVariantExitCode {
+    #[error(exit_code = 64)]
+    Foo,
+    Bar,
+}
+
+#[Error(exit_code = 2)]
+struct StructErrorWithExitCode;
+
+#[test]
+fn it_generates_exit_code() {
+    assert_eq!(EnumErrorWithExitCodes::Foo.exit_code(), 70);
+    assert_eq!(EnumErrorWithExitCodes::Bar { a: "A" }.exit_code(), 78);
+    assert_eq!(EnumErrorWithExitCodes::Baz("B").exit_code(), 70);
+    assert_eq!(EnumErrorWithExitCodes::Qux.exit_code(), 0);
+    assert_eq!(EnumErrorWithExitCodes::Quux.exit_code(), 70);
+}
+
+#[test]
+fn it_defaults_exit_code_to_one() {
+    assert_eq!(EnumErrorWithVariantExitCode::Foo.exit_code(), 64);
+    assert_eq!(EnumErrorWithVariantExitCode::Bar.exit_code(), 1);
+    assert_eq!(StructErrorWithExitCode.exit_code(), 2);
+}
+
+#[test]
+fn it_converts_into_boxed_error() {
+    let boxed: Box<dyn std::error::Error> = EnumError::Foo.into();
+    assert_eq!(boxed.to_string(), "EnumError::Foo");
+
+    let boxed: Box<dyn std::error::Error + Send + Sync> = EnumError::Foo.into();
+    assert_eq!(boxed.to_string(), "EnumError::Foo");
+}
+
+#[test]
+fn it_formats_local_error() {
+    #[Error(desc = "Failed to parse", code = 10)]
+    enum LocalError {
+        Foo { a: &'static str },
+        Int(#[from] std::num::ParseIntError),
+    }
+
+    fn parse(input: &str) -> Result<u8, Box<dyn std::error::Error>> {
+        if input.is_empty() {
+            return Err(LocalError::Foo { a: "empty" }.into());
+        }
+
+        Ok(input.parse().map_err(LocalError::from)?)
+    }
+
+    let actual = parse("").unwrap_err().to_string();
+    let expected = indoc! {r#"
+        [E10] LocalError::Foo
+        Failed to parse
+        === ↴
+        a: empty"#};
+
+    assert_eq!(actual, expected);
+
+    let error = parse("x").unwrap_err();
+
+    assert!(error.source().is_some());
+    assert_eq!(parse("7").unwrap(), 7);
+}
+
+#[Error(desc = "My enum error", debug_same_as_display)]
+enum EnumErrorWithDebugAsDisplay {
+    Foo { a: &'static str },
+}
+
+#[Error(debug_same_as_display)]
+struct StructErrorWithDebugAsDisplay<T>(T);
+
+#[test]
+fn it_formats_debug_same_as_display() {
+    let error = EnumErrorWithDebugAsDisplay::Foo { a: "A" };
+    let expected = indoc! {r#"
+        EnumErrorWithDebugAsDisplay::Foo
+        My enum error
+        === ↴
+        a: A"#};
+
+    assert_eq!(format!("{:?}", error), expected);
+    assert_eq!(format!("{:?}", error), format!("{}", error));
+
+    let error = StructErrorWithDebugAsDisplay("A");
+
+    assert_error(&error);
+    assert_eq!(
+        format!("{:?}", error),
+        "StructErrorWithDebugAsDisplay\n=== ↴\nA"
+    );
+    assert_eq!(format!("{:?}", error), format!("{}", error));
+}
+
+#[Error]
+struct PhantomStructError<T>(std::marker::PhantomData<T>, String);
+
+#[Error]
+enum PhantomEnumError<T> {
+    Foo {
+        a: &'static str,
+        marker: std::marker::PhantomData<T>,
+    },
+}
+
+#[test]
+fn it_skips_phantom_data_fields() {
+    let error = PhantomStructError::<u8>(std::marker::PhantomData, "A".to_string());
+    let expected = indoc! {r#"
+        PhantomStructError
+        === ↴
+        A"#};
+
+    assert_error(&error);
+    assert_eq!(format!("{}", error), expected);
+
+    let error = PhantomEnumError::<u8>::Foo {
+        a: "A",
+        marker: std::marker::PhantomData,
+    };
+    let expected = indoc! {r#"
+        PhantomEnumError::Foo
+        === ↴
+        a: A"#};
+
+    assert_eq!(format!("{}", error), expected);
+}
+
+#[Error(fmt = debug)]
+enum EnumErrorWithFmtPriority {
+    Foo {
+        a: &'static str,
+    },
+    #[error(fmt = display)]
+    Bar {
+        a: &'static str,
+        #[fmt(debug)]
+        b: &'static str,
+    },
+}
+
+#[Error(all_fields_debug)]
+enum EnumErrorWithAllFieldsDebug {
+    #[error(fmt = display)]
+    Foo {
+        a: &'static str,
+        #[fmt(display)]
+        b: &'static str,
+    },
+}
+
+#[test]
+fn it_prioritizes_field_fmt_over_variant_and_root_fmt() {
+    let actual = format!("{}", EnumErrorWithFmtPriority::Foo { a: "A" });
+    let expected = indoc! {r#"
+        EnumErrorWithFmtPriority::Foo
+        === ↴
+        a: "A""#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithFmtPriority::Bar { a: "A", b: "B" });
+    let expected = indoc! {r#"
+        EnumErrorWithFmtPriority::Bar
+        === ↴
+        a: A
+        b: "B""#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_all_fields_with_debug_despite_variant_fmt() {
+    let actual = format!("{}", EnumErrorWithAllFieldsDebug::Foo { a: "A", b: "B" });
+    let expected = indoc! {r#"
+        EnumErrorWithAllFieldsDebug::Foo
+        === ↴
+        a: "A"
+        b: B"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(fmt = debug, anyhow_compat)]
+enum EnumErrorWithAnyhow {
+    Foo { a: u8, inner: anyhow::Error },
+}
+
+#[test]
+fn it_formats_anyhow_error_with_chain() {
+    let error = EnumErrorWithAnyhow::Foo {
+        a: 1,
+        inner: anyhow::anyhow!("No such file").context("Failed to read config"),
+    };
+    let expected = indoc! {r#"
+        EnumErrorWithAnyhow::Foo
+        === ↴
+        a: 1
+        inner: Failed to read config: No such file"#};
+
+    assert_eq!(format!("{}", error), expected);
+}
+
+#[Error(desc = "Quota exceeded")]
+enum EnumErrorWithFieldsFormat {
+    #[fmt("used: {used}/{total} MB")]
+    Named { used: u32, total: u32 },
+    #[fmt("used: {}/{:>4} MB")]
+    Unnamed(u32, u32),
+}
+
+#[test]
+fn it_formats_fields_with_variant_format() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithFieldsFormat::Named {
+            used: 512,
+            total: 1024
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithFieldsFormat::Named
+        Quota exceeded
+        used: 512/1024 MB"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithFieldsFormat::Unnamed(5, 10));
+    let expected = indoc! {r#"
+        EnumErrorWithFieldsFormat::Unnamed
+        Quota exceeded
+        used: 5/  10 MB"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(send_sync)]
+enum EnumErrorWithSendSync<'a, T> {
+    Foo { a: &'a str },
+    Bar(T),
+}
+
+#[test]
+fn it_allows_send_sync_fields() {
+    let error: EnumErrorWithSendSync<usize> = EnumErrorWithSendSync::Foo { a: "A" };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        EnumErrorWithSendSync::Foo
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+    assert_send_sync(&EnumErrorWithSendSync::<usize>::Bar(42));
+}
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+mod visibility {
+    #[Error(code = 10, exit_code = 2, variant_name, result_alias = "VisibleResult")]
+    pub(crate) struct StructErrorWithVisibility;
+
+    #[Error(predicates, accessors, variant_name)]
+    pub(crate) enum EnumErrorWithVisibility {
+        Foo,
+        Bar { a: &'static str },
+    }
+}
+
+use visibility::{EnumErrorWithVisibility, StructErrorWithVisibility, VisibleResult};
+
+#[test]
+fn it_generates_items_with_visibility_of_the_type() {
+    fn fail() -> VisibleResult<()> {
+        Err(StructErrorWithVisibility)
+    }
+
+    let error = fail().unwrap_err();
+
+    assert_eq!(error.error_code(), Some(10));
+    assert_eq!(error.exit_code(), 2);
+    assert_eq!(error.variant_name(), "StructErrorWithVisibility");
+
+    let error = EnumErrorWithVisibility::Bar { a: "A" };
+
+    assert!(error.is_bar());
+    assert!(!EnumErrorWithVisibility::Foo.is_bar());
+    assert_eq!(error.as_bar(), Some(&"A"));
+    assert_eq!(error.variant_name(), "Bar");
+}
+
+#[Error(deprecated = "Use EnumError instead", desc = "Old error")]
+enum DeprecatedEnumError {
+    Foo(#[from] std::fmt::Error),
+    #[error(deprecated = "Use Foo instead")]
+    Bar {
+        a: &'static str,
+    },
+}
+
+#[test]
+#[allow(deprecated)]
+fn it_formats_deprecated_error() {
+    let error = DeprecatedEnumError::from(std::fmt::Error);
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        DeprecatedEnumError::Foo
+        Old error
+        === ↴
+        an error occurred when formatting an argument"#};
+
+    assert_eq!(actual, expected);
+    assert!(error.source().is_some());
+
+    let actual = format!("{}", DeprecatedEnumError::Bar { a: "A" });
+    let expected = indoc! {r#"
+        DeprecatedEnumError::Bar
+        Old error
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(fmt = debug, desc = "My error")]
+struct StructErrorWithFmtBeforeDesc(&'static str);
+
+#[Error(desc = "My error", fmt = debug)]
+struct StructErrorWithDescBeforeFmt(&'static str);
+
+#[Error(desc = "My error")]
+struct StructErrorWithDescOnly(&'static str);
+
+#[Error(fmt = debug)]
+struct StructErrorWithFmtOnly(&'static str);
+
+#[Error]
+struct StructErrorWithoutArgs(&'static str);
+
+#[test]
+fn it_parses_args_in_any_order() {
+    let actual = format!("{}", StructErrorWithFmtBeforeDesc("Hey!"));
+    let expected = indoc! {r#"
+        StructErrorWithFmtBeforeDesc
+        My error
+        === ↴
+        "Hey!""#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", StructErrorWithDescBeforeFmt("Hey!"));
+    let expected = indoc! {r#"
+        StructErrorWithDescBeforeFmt
+        My error
+        === ↴
+        "Hey!""#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_parses_single_or_no_args() {
+    let actual = format!("{}", StructErrorWithDescOnly("Hey!"));
+    let expected = indoc! {r#"
+        StructErrorWithDescOnly
+        My error
+        === ↴
+        Hey!"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", StructErrorWithFmtOnly("Hey!"));
+    let expected = indoc! {r#"
+        StructErrorWithFmtOnly
+        === ↴
+        "Hey!""#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", StructErrorWithoutArgs("Hey!"));
+    let expected = indoc! {r#"
+        StructErrorWithoutArgs
+        === ↴
+        Hey!"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error]
+struct StructErrorWithRadixFmt {
+    #[fmt(hex)]
+    hex: u32,
+    #[fmt(upper_hex)]
+    upper_hex: u32,
+    #[fmt(octal)]
+    octal: u32,
+    #[fmt(binary)]
+    binary: u8,
+}
+
+#[Error(fmt = pointer)]
+enum EnumErrorWithPointerFmt {
+    Foo(&'static str),
+}
+
+#[test]
+fn it_formats_fields_with_radix() {
+    let actual = format!(
+        "{}",
+        StructErrorWithRadixFmt {
+            hex: 255,
+            upper_hex: 255,
+            octal: 8,
+            binary: 5,
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithRadixFmt
+        === ↴
+        hex: ff
+        upper_hex: FF
+        octal: 10
+        binary: 101"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_fields_as_pointers() {
+    let actual = format!("{}", EnumErrorWithPointerFmt::Foo("Hey!"));
+
+    assert!(actual.starts_with("EnumErrorWithPointerFmt::Foo\n=== ↴\n0x"));
+}
+
+#[Error(code = 1, exit_code = 2, predicates, accessors, variant_name)]
+enum EnumErrorWithCfg {
+    Foo,
+    #[cfg(test)]
+    #[error(desc = "Test only")]
+    Bar {
+        a: &'static str,
+    },
+    #[cfg(not(test))]
+    #[error(desc = "Never in tests")]
+    Baz(&'static str),
+}
+
+#[test]
+fn it_keeps_cfg_attrs_on_variants() {
+    let actual = format!("{}", EnumErrorWithCfg::Bar { a: "A" });
+    let expected = indoc! {r#"
+        [E2] EnumErrorWithCfg::Bar
+        Test only
+        === ↴
+        a: A"#};
+
+    assert_eq!(actual, expected);
+    assert_eq!(EnumErrorWithCfg::Bar { a: "A" }.error_code(), Some(2));
+    assert!(EnumErrorWithCfg::Bar { a: "A" }.is_bar());
+    assert!(EnumErrorWithCfg::Foo.as_bar().is_none());
+    assert_eq!(EnumErrorWithCfg::Foo.variant_name(), "Foo");
+    assert_eq!(EnumErrorWithCfg::Foo.exit_code(), 2);
+}
+
+#[Error(desc = "My raw error", code = 1)]
+enum EnumErrorWithRaw {
+    #[error(raw = "Failed to read {path}: {reason:?}")]
+    Read {
+        path: &'static str,
+        #[fmt(debug)]
+        reason: &'static str,
+    },
+    /// Ignored doc comment
+    #[error(raw = "Failed to write {0} bytes, {{0}} written")]
+    Write(usize),
+    Other(&'static str),
+}
+
+#[test]
+fn it_formats_raw_variant_with_named_fields() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithRaw::Read {
+            path: "a.txt",
+            reason: "missing"
+        }
+    );
+    let expected = r#"Failed to read a.txt: "missing""#;
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_raw_variant_with_positional_fields() {
+    let actual = format!("{}", EnumErrorWithRaw::Write(42));
+    let expected = "Failed to write 42 bytes, {0} written";
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithRaw::Other("Oh no"));
+    let expected = indoc! {r#"
+        [E3] EnumErrorWithRaw::Other
+        My raw error
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(category = "network")]
+enum EnumErrorWithCategory {
+    Timeout,
+    Refused {
+        port: u16,
+    },
+    #[error(category = "db")]
+    Query(&'static str),
+}
+
+#[Error(category = "network", show_category, help = "Retry later")]
+enum EnumErrorWithShownCategory {
+    Timeout,
+    #[error(category = "db")]
+    Query(&'static str),
+}
+
+#[Error]
+enum EnumErrorWithVariantCategory {
+    #[error(category = "db")]
+    Query,
+    Other,
+}
+
+#[test]
+fn it_generates_category() {
+    assert_eq!(EnumErrorWithCategory::Timeout.category(), "network");
+    assert_eq!(
+        EnumErrorWithCategory::Refused { port: 80 }.category(),
+        "network"
+    );
+    assert_eq!(EnumErrorWithCategory::Query("Oh no").category(), "db");
+    assert_eq!(EnumErrorWithVariantCategory::Query.category(), "db");
+    assert_eq!(EnumErrorWithVariantCategory::Other.category(), "");
+}
+
+#[test]
+fn it_hides_category_by_default() {
+    let actual = format!("{}", EnumErrorWithCategory::Timeout);
+    let expected = "EnumErrorWithCategory::Timeout";
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_shows_category() {
+    let actual = format!("{}", EnumErrorWithShownCategory::Timeout);
+    let expected = indoc! {r#"
+        EnumErrorWithShownCategory::Timeout
+        category: network
+        hint: Retry later"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithShownCategory::Query("Oh no"));
+    let expected = indoc! {r#"
+        EnumErrorWithShownCategory::Query
+        category: db
+        hint: Retry later
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = "My enum error")]
+enum EnumErrorWithTransparentVariant {
+    /// Ignored doc comment
+    #[error(transparent)]
+    Io(std::io::Error),
+    #[error(transparent)]
+    Fmt {
+        inner: std::fmt::Error,
+    },
+    Other(&'static str),
+}
+
+#[test]
+fn it_formats_transparent_variant() {
+    let io = std::io::Error::other("oops");
+    let error = EnumErrorWithTransparentVariant::Io(io);
+
+    assert_eq!(format!("{}", error), "oops");
+    assert!(error.source().is_none());
+
+    let actual = format!(
+        "{}",
+        EnumErrorWithTransparentVariant::Fmt {
+            inner: std::fmt::Error
+        }
+    );
+    let expected = "an error occurred when formatting an argument";
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithTransparentVariant::Other("Oh no"));
+    let expected = indoc! {r#"
+        EnumErrorWithTransparentVariant::Other
+        My enum error
+        === ↴
+        Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(impl_display_only, desc = "My display error")]
+enum DisplayOnlyError {
+    Foo { a: &'static str },
+    Bar(usize),
+}
+
+#[test]
+fn it_formats_display_only_error() {
+    let actual = format!("{}", DisplayOnlyError::Foo { a: "Hey!" });
+    let expected = indoc! {r#"
+        DisplayOnlyError::Foo
+        My display error
+        === ↴
+        a: Hey!"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", DisplayOnlyError::Bar(42));
+    let expected = indoc! {r#"
+        DisplayOnlyError::Bar
+        My display error
+        === ↴
+        42"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error]
+struct StructErrorWithFmtSpec {
+    #[fmt(width = 5, align = right)]
+    a: &'static str,
+    #[fmt(fill = '0', width = 5)]
+    b: usize,
+    #[fmt(precision = 2)]
+    c: f64,
+    #[fmt(fill = '*', align = center, width = 7, precision = 3)]
+    d: &'static str,
+}
+
+#[test]
+fn it_formats_fields_with_fmt_spec() {
+    let actual = format!(
+        "{}",
+        StructErrorWithFmtSpec {
+            a: "A",
+            b: 42,
+            c: 1.23456,
+            d: "Hello!"
+        }
+    );
+    let expected = indoc! {r#"
+        StructErrorWithFmtSpec
+        === ↴
+        a:     A
+        b: 00042
+        c: 1.23
+        d: **Hel**"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error]
+enum EnumErrorWithLabels {
+    Foo {
+        #[label = "request timeout (ms)"]
+        req_timeout_ms: usize,
+        attempts: usize,
+    },
+    Bar(#[label = "path {}"] &'static str),
+}
+
+#[test]
+fn it_formats_fields_with_labels() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithLabels::Foo {
+            req_timeout_ms: 5000,
+            attempts: 3
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithLabels::Foo
+        === ↴
+        request timeout (ms): 5000
+        attempts: 3"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithLabels::Bar("a.txt"));
+    let expected = indoc! {r#"
+        EnumErrorWithLabels::Bar
+        === ↴
+        path {}: a.txt"#};
+
+    assert_eq!(actual, expected);
+}
+
+mod messages {
+    pub const TIMEOUT: &str = "Request timed out";
+    pub const FAILED: &str = "Request failed";
+}
+
+#[Error(desc = messages::FAILED)]
+enum EnumErrorWithConstDesc {
+    #[error(desc = messages::TIMEOUT)]
+    Timeout {
+        ms: usize,
+    },
+    Other,
+}
+
+#[test]
+fn it_formats_const_desc() {
+    let actual = format!("{}", EnumErrorWithConstDesc::Timeout { ms: 5000 });
+    let expected = indoc! {r#"
+        EnumErrorWithConstDesc::Timeout
+        EnumErrorWithConstDesc: Request failed
+        Timeout: Request timed out
+        === ↴
+        ms: 5000"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithConstDesc::Other);
+    let expected = indoc! {r#"
+        EnumErrorWithConstDesc::Other
+        Request failed"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = messages::TIMEOUT, fmt = json)]
+struct JsonStructErrorWithConstDesc {
+    ms: usize,
+}
+
+#[test]
+fn it_formats_const_desc_as_json() {
+    let actual = format!("{}", JsonStructErrorWithConstDesc { ms: 5000 });
+    let expected = r#"{"type":"JsonStructErrorWithConstDesc","desc":"Request timed out","fields":{"ms":5000}}"#;
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(constructor, builder)]
+struct StructErrorWithConstructor {
+    path: &'static str,
+    #[fmt(debug)]
+    attempts: Vec<usize>,
+}
+
+#[Error(constructor, builder)]
+struct TupleStructErrorWithConstructor(&'static str, usize);
+
+#[test]
+fn it_constructs_struct_error() {
+    let expected = indoc! {r#"
+        StructErrorWithConstructor
+        === ↴
+        path: a.txt
+        attempts: [
+            1,
+        ]"#};
+
+    let error = StructErrorWithConstructor::new("a.txt", vec![1]);
+    assert_eq!(format!("{}", error), expected);
+
+    let error = StructErrorWithConstructor::builder()
+        .with_attempts(vec![1])
+        .with_path("a.txt")
+        .build();
+    assert_eq!(format!("{}", error), expected);
+}
+
+#[test]
+fn it_constructs_tuple_struct_error() {
+    let expected = indoc! {r#"
+        TupleStructErrorWithConstructor
+        === ↴
+        0: a.txt
+        1: 42"#};
+
+    let error = TupleStructErrorWithConstructor::new("a.txt", 42);
+    assert_eq!(format!("{}", error), expected);
+
+    let error = TupleStructErrorWithConstructor::builder()
+        .with_0("a.txt")
+        .with_1(42)
+        .build();
+    assert_eq!(format!("{}", error), expected);
+}
+
+#[test]
+#[should_panic(expected = "`path` is not set")]
+fn it_panics_on_build_with_missing_field() {
+    let _ = StructErrorWithConstructor::builder()
+        .with_attempts(vec![1])
+        .build();
+}
+
+#[Error(desc = "Failed to load config", context_map, constructor)]
+struct StructErrorWithContext {
+    path: &'static str,
+}
+
+fn load_config() -> Result<(), StructErrorWithContext> {
+    Err(StructErrorWithContext::new("config.toml").with_context("attempt", 3))
+}
+
+fn start_app() -> Result<(), StructErrorWithContext> {
+    load_config().map_err(|mut error| {
+        error += ("stage", Box::new("startup"));
+        error.with_context("user", "admin")
+    })
+}
+
+#[test]
+fn it_formats_context_map() {
+    let actual = format!("{}", start_app().unwrap_err());
+    let expected = indoc! {r#"
+        StructErrorWithContext
+        Failed to load config
+        === ↴
+        path: config.toml
+        attempt: 3
+        stage: startup
+        user: admin"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{:?}", start_app().unwrap_err());
+    let expected = r#"StructErrorWithContext { path: "config.toml", context: {"attempt": "3", "stage": "startup", "user": "admin"} }"#;
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_formats_empty_context_map() {
+    let actual = format!("{}", StructErrorWithContext::new("config.toml"));
+    let expected = indoc! {r#"
+        StructErrorWithContext
+        Failed to load config
+        === ↴
+        path: config.toml"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(hash, derives(PartialEq, Eq))]
+enum HashEnumError {
+    Foo,
+    Bar { code: usize },
+}
+
+#[test]
+fn it_derives_hash() {
+    let mut counts = std::collections::HashMap::new();
+
+    for error in [
+        HashEnumError::Foo,
+        HashEnumError::Bar { code: 1 },
+        HashEnumError::Foo,
+    ] {
+        *counts.entry(error).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.get(&HashEnumError::Foo), Some(&2));
+    assert_eq!(counts.get(&HashEnumError::Bar { code: 1 }), Some(&1));
+    assert_eq!(counts.get(&HashEnumError::Bar { code: 2 }), None);
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct DebugOnly(usize);
+
+#[Error(fmt = debug)]
+enum EnumErrorWithDebugOnlyFields {
+    Foo(DebugOnly),
+    Bar(DebugOnly, #[fmt(display)] usize),
+}
+
+#[test]
+fn it_formats_debug_only_fields_with_root_debug_fmt() {
+    let actual = format!("{}", EnumErrorWithDebugOnlyFields::Foo(DebugOnly(42)));
+    let expected = indoc! {r#"
+        EnumErrorWithDebugOnlyFields::Foo
+        === ↴
+        DebugOnly(
+            42,
+        )"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithDebugOnlyFields::Bar(DebugOnly(42), 1));
+    let expected = indoc! {r#"
+        EnumErrorWithDebugOnlyFields::Bar
+        === ↴
+        0: DebugOnly(
+            42,
+        )
+        1: 1"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = "Request failed")]
+enum EnumErrorWithReason {
+    Foo {
+        #[reason]
+        message: String,
+        status: usize,
+    },
+    Bar(#[reason] &'static str),
+}
+
+#[Error]
+struct StructErrorWithReason {
+    #[reason]
+    reason: &'static str,
+}
+
+#[test]
+fn it_formats_reason_in_title() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithReason::Foo {
+            message: "Service is unavailable".to_string(),
+            status: 503
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithReason::Foo: Service is unavailable
+        Request failed
+        === ↴
+        status: 503"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithReason::Bar("Timed out"));
+    let expected = indoc! {r#"
+        EnumErrorWithReason::Bar: Timed out
+        Request failed"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", StructErrorWithReason { reason: "Oh no" });
+    let expected = "StructErrorWithReason: Oh no";
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(separator = " - ")]
+enum EnumErrorWithSeparator {
+    Foo,
+    Bar(usize),
+}
+
+#[Error(separator = " - ", desc = "My struct error")]
+struct StructErrorWithSeparator;
+
+#[test]
+fn it_formats_title_with_separator() {
+    let actual = format!("{}", EnumErrorWithSeparator::Foo);
+    let expected = "EnumErrorWithSeparator - Foo";
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithSeparator::Bar(42));
+    let expected = indoc! {r#"
+        EnumErrorWithSeparator - Bar
+        === ↴
+        42"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", StructErrorWithSeparator);
+    let expected = indoc! {r#"
+        StructErrorWithSeparator
+        My struct error"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = "My custom error")]
+struct CustomError;
+
+#[Error]
+enum EnumErrorWithExternalErrors {
+    ParseInt(std::num::ParseIntError),
+    Io(std::io::Error),
+    Custom(CustomError),
+    #[error(fmt = debug)]
+    DebugParseInt(std::num::ParseIntError),
+    Both(std::num::ParseIntError, #[fmt(debug)] std::io::ErrorKind),
+}
+
+#[test]
+fn it_formats_external_errors_as_unnamed_fields() {
+    let parse_int = || "x".parse::<usize>().unwrap_err();
+
+    let actual = format!("{}", EnumErrorWithExternalErrors::ParseInt(parse_int()));
+    let expected = indoc! {r#"
+        EnumErrorWithExternalErrors::ParseInt
+        === ↴
+        invalid digit found in string"#};
+
+    assert_eq!(actual, expected);
+
+    let io = std::io::Error::other("oops");
+    let actual = format!("{}", EnumErrorWithExternalErrors::Io(io));
+    let expected = indoc! {r#"
+        EnumErrorWithExternalErrors::Io
+        === ↴
+        oops"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithExternalErrors::Custom(CustomError));
+    let expected = indoc! {r#"
+        EnumErrorWithExternalErrors::Custom
+        === ↴
+        CustomError
+        My custom error"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!(
+        "{}",
+        EnumErrorWithExternalErrors::DebugParseInt(parse_int())
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithExternalErrors::DebugParseInt
+        === ↴
+        ParseIntError {
+            kind: InvalidDigit,
+        }"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!(
+        "{}",
+        EnumErrorWithExternalErrors::Both(parse_int(), std::io::ErrorKind::NotFound)
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithExternalErrors::Both
+        === ↴
+        0: invalid digit found in string
+        1: NotFound"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(sources)]
+enum ConfigErrorWithSources {
+    Read(#[from] std::io::Error),
+}
+
+#[test]
+fn it_iterates_through_sources() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "oops");
+    let error = ConfigErrorWithSources::from(io);
+
+    let sources: Vec<_> = error.sources().collect();
+
+    assert_eq!(sources.len(), 2);
+    assert!(sources[0].is::<ConfigErrorWithSources>());
+    assert_eq!(
+        sources[1]
+            .downcast_ref::<std::io::Error>()
+            .map(std::io::Error::kind),
+        Some(std::io::ErrorKind::NotFound)
+    );
+}
+
+#[Error(equality)]
+enum EqualityEnumError {
+    Foo { code: usize },
+    Boxed(Box<dyn std::error::Error + Send + Sync>),
+    Float(f64),
+    Bar,
+}
+
+#[test]
+fn it_compares_errors_with_equality() {
+    assert_eq!(
+        EqualityEnumError::Foo { code: 1 },
+        EqualityEnumError::Foo { code: 1 }
+    );
+    assert_ne!(
+        EqualityEnumError::Foo { code: 1 },
+        EqualityEnumError::Foo { code: 2 }
+    );
+    assert_ne!(EqualityEnumError::Foo { code: 1 }, EqualityEnumError::Bar);
+    assert_eq!(EqualityEnumError::Bar, EqualityEnumError::Bar);
+
+    // `Box<dyn Error>` is not `PartialEq`, so the `Debug` outputs are compared
+    assert_eq!(
+        EqualityEnumError::Boxed("Oh no".into()),
+        EqualityEnumError::Boxed("Oh no".into())
+    );
+    assert_ne!(
+        EqualityEnumError::Boxed("Oh no".into()),
+        EqualityEnumError::Boxed("Oh yes".into())
+    );
+
+    // `f64` is not `Eq`, so `NaN` is equal to itself as required by `Eq`
+    assert_eq!(
+        EqualityEnumError::Float(f64::NAN),
+        EqualityEnumError::Float(f64::NAN)
+    );
+    assert_ne!(EqualityEnumError::Float(1.0), EqualityEnumError::Float(2.0));
+}
+
+#[Error(clone)]
+struct CloneStructError {
+    path: String,
+}
+
+#[Error(clone)]
+enum CloneEnumError {
+    Foo,
+    Bar { message: String, code: usize },
+    Baz(CloneStructError),
+}
+
+#[test]
+fn it_derives_clone() {
+    let error = CloneStructError {
+        path: "a.txt".to_string(),
+    };
+    assert_eq!(format!("{}", error.clone()), format!("{}", error));
+
+    let error = CloneEnumError::Bar {
+        message: "Oh no".to_string(),
+        code: 42,
+    };
+    assert_eq!(format!("{}", error.clone()), format!("{}", error));
+
+    let error = CloneEnumError::Baz(CloneStructError {
+        path: "a.txt".to_string(),
+    });
+    assert_eq!(format!("{}", error.clone()), format!("{}", error));
+    assert_eq!(
+        format!("{}", CloneEnumError::Foo.clone()),
+        "CloneEnumError::Foo"
+    );
+}
+
+#[Error]
+enum EnumErrorWithFmtSkip {
+    Foo {
+        a: &'static str,
+        #[fmt(skip)]
+        b: Vec<u8>,
+    },
+    Bar(#[fmt(skip)] Vec<u8>),
+}
+
+#[test]
+fn it_skips_fields_with_fmt_skip() {
+    let actual = format!(
+        "{}",
+        EnumErrorWithFmtSkip::Foo {
+            a: "Hey!",
+            b: vec![1]
+        }
+    );
+    let expected = indoc! {r#"
+        EnumErrorWithFmtSkip::Foo
+        === ↴
+        a: Hey!"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithFmtSkip::Bar(vec![1]));
+    let expected = "EnumErrorWithFmtSkip::Bar";
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = "Failed to parse config")]
+enum AggregateError {
+    Field {
+        name: &'static str,
+    },
+    #[error(aggregate)]
+    Many(Vec<AggregateError>),
+    #[error(aggregate, desc = "Multiple errors")]
+    Boxed {
+        errors: Vec<Box<dyn std::error::Error>>,
+    },
+    #[error(aggregate, compact)]
+    Compact(Vec<&'static str>),
+}
+
+#[test]
+fn it_numbers_errors_of_aggregate_variant() {
+    let actual = format!(
+        "{}",
+        AggregateError::Many(vec![
+            AggregateError::Field { name: "a" },
+            AggregateError::Field { name: "b" },
+        ])
+    );
+    let expected = indoc! {r#"
+        AggregateError::Many
+        Failed to parse config
+        === ↴
+        1. AggregateError::Field
+        Failed to parse config
+        === ↴
+        name: a
+        2. AggregateError::Field
+        Failed to parse config
+        === ↴
+        name: b"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!(
+        "{}",
+        AggregateError::Boxed {
+            errors: vec!["first error".into(), "second error".into()],
+        }
+    );
+    let expected = indoc! {r#"
+        AggregateError::Boxed
+        AggregateError: Failed to parse config
+        Boxed: Multiple errors
+        === ↴
+        1. first error
+        2. second error"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn it_numbers_errors_of_compact_aggregate_variant() {
+    let actual = format!(
+        "{}",
+        AggregateError::Compact(vec!["first error", "second error"])
+    );
+    let expected = "AggregateError::Compact Failed to parse config 1. first error 2. second error";
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(desc = "Derived after the macro")]
+#[derive(Debug, Clone, thiserror::Error)]
+enum EnumErrorWithExistingDerives {
+    Foo { a: usize },
+}
+
+// Derives above `#[Error]` are expanded before it, so `no_debug` is required
+#[derive(Debug, Clone)]
+#[Error(desc = "Derived before the macro", no_debug)]
+struct StructErrorWithExistingDerives {
+    a: usize,
+}
+
+#[test]
+fn it_skips_existing_derives() {
+    let error = EnumErrorWithExistingDerives::Foo { a: 42 };
+    let expected = indoc! {r#"
+        EnumErrorWithExistingDerives::Foo
+        Derived after the macro
+        === ↴
+        a: 42"#};
+
+    assert_eq!(format!("{}", error.clone()), expected);
+    assert_eq!(format!("{:?}", error), "Foo { a: 42 }");
+
+    let error = StructErrorWithExistingDerives { a: 42 };
+    let expected = indoc! {r#"
+        StructErrorWithExistingDerives
+        Derived before the macro
+        === ↴
+        a: 42"#};
+
+    assert_eq!(format!("{}", error.clone()), expected);
+    assert_eq!(
+        format!("{:?}", error),
+        "StructErrorWithExistingDerives { a: 42 }"
+    );
+}
+
+#[Error(desc = "Exit status")]
+#[derive(Clone, Copy)]
+enum EnumErrorWithDiscriminants {
+    Ok = 0,
+    Failure = 1,
+    #[error(desc = "Terminated by a signal")]
+    Signal = 128,
+}
+
+#[test]
+fn it_preserves_discriminants() {
+    assert_eq!(EnumErrorWithDiscriminants::Ok as i32, 0);
+    assert_eq!(EnumErrorWithDiscriminants::Failure as i32, 1);
+    assert_eq!(EnumErrorWithDiscriminants::Signal as i32, 128);
+
+    let actual = format!("{}", EnumErrorWithDiscriminants::Failure);
+    let expected = indoc! {r#"
+        EnumErrorWithDiscriminants::Failure
+        Exit status"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", EnumErrorWithDiscriminants::Signal);
+    let expected = indoc! {r#"
+        EnumErrorWithDiscriminants::Signal
+        EnumErrorWithDiscriminants: Exit status
+        Signal: Terminated by a signal"#};
+
+    assert_eq!(actual, expected);
+}
+
+#[Error(fmt = debug)]
+struct UnnamedStructErrorWithDebug(Vec<u8>);
+
+#[Error(fmt = debug, compact)]
+struct CompactUnnamedStructErrorWithDebug(Vec<u8>);
+
+#[test]
+fn it_formats_unnamed_struct_field_with_root_fmt() {
+    let actual = format!("{}", UnnamedStructErrorWithDebug(vec![1, 2]));
+    let expected = indoc! {r#"
+        UnnamedStructErrorWithDebug
+        === ↴
+        [
+            1,
+            2,
+        ]"#};
+
+    assert_eq!(actual, expected);
+
+    let actual = format!("{}", CompactUnnamedStructErrorWithDebug(vec![1, 2]));
+    let expected = "CompactUnnamedStructErrorWithDebug [1, 2]";
+
+    assert_eq!(actual, expected);
+}
+
+// `strum::Display` is not available here, so `Display` is implemented by hand
+#[Error(strum_display, code = 10)]
+enum EnumErrorWithStrumDisplay {
+    NotFound { path: &'static str },
+    Io(#[from] std::io::Error),
+}
+
+impl std::fmt::Display for EnumErrorWithStrumDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EnumErrorWithStrumDisplay::NotFound { path } => write!(f, "{} not found", path),
+            EnumErrorWithStrumDisplay::Io(_) => f.write_str("I/O error"),
+        }
+    }
+}
+
+#[test]
+fn it_keeps_external_display_with_strum_display() {
+    let error = EnumErrorWithStrumDisplay::NotFound { path: "a.txt" };
+
+    assert_eq!(format!("{}", error), "a.txt not found");
+    assert_eq!(error.to_string(), "a.txt not found");
+    assert_eq!(error.error_code(), Some(10));
+
+    let error: EnumErrorWithStrumDisplay = std::io::Error::other("Oh no").into();
+
+    assert_eq!(error.to_string(), "I/O error");
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+mod api {
+    use justerror::Error;
+
+    #[Error(
+        desc = "Request failed",
+        code = 500,
+        predicates,
+        result_alias = "ApiResult"
+    )]
+    pub enum ApiError {
+        NotFound { path: &'static str },
+        Io(#[from] std::io::Error),
+    }
+}
+
+use api::{ApiError, ApiResult};
+
+fn read_api(path: &'static str) -> ApiResult<()> {
+    if path.is_empty() {
+        Err(std::io::Error::other("Oh no"))?;
+    }
+
+    Err(ApiError::NotFound { path })
+}
+
+#[test]
+fn it_resolves_reexported_error() {
+    let error = read_api("a.txt").unwrap_err();
+    let expected = indoc! {r#"
+        [E500] ApiError::NotFound
+        Request failed
+        === ↴
+        path: a.txt"#};
+
+    assert_eq!(error.to_string(), expected);
+    assert!(error.is_not_found());
+    assert_eq!(error.error_code(), Some(500));
+
+    let error = read_api("").unwrap_err();
+
+    assert!(error.is_io());
+    assert!(error.source().is_some());
+}
+
+#[Error(impl_std_error, desc = "Implemented without thiserror")]
+enum EnumErrorWithStdErrorImpl {
+    Read {
+        path: &'static str,
+        #[source]
+        cause: std::io::Error,
+    },
+    Parse(#[from] std::num::ParseIntError),
+    Other,
+}
+
+#[test]
+fn it_implements_std_error_without_thiserror() {
+    let error = EnumErrorWithStdErrorImpl::Read {
+        path: "a.txt",
+        cause: std::io::Error::other("Oh no"),
+    };
+    let expected = indoc! {r#"
+        EnumErrorWithStdErrorImpl::Read
+        Implemented without thiserror
+        === ↴
+        path: a.txt
+        cause: Oh no"#};
+
+    assert_eq!(error.to_string(), expected);
+    assert_eq!(error.source().unwrap().to_string(), "Oh no");
+
+    let error: EnumErrorWithStdErrorImpl = "x".parse::<usize>().unwrap_err().into();
+
+    assert!(error.source().is_some());
+    assert!(EnumErrorWithStdErrorImpl::Other.source().is_none());
+}
+
+macro_rules! define_error {
+    ($name:ident { $($variant:ident($ty:ty)),* $(,)? }) => {
+        #[Error(desc = "Defined by a macro", code = 10, predicates, accessors)]
+        enum $name {
+            $($variant { value: $ty },)*
+        }
+    };
+}
+
+define_error!(MacroRulesError {
+    Parse(&'static str),
+    Limit(usize),
+});
+
+#[test]
+fn it_expands_inside_macro_rules() {
+    let error = MacroRulesError::Limit { value: 42 };
+    let expected = indoc! {r#"
+        [E11] MacroRulesError::Limit
+        Defined by a macro
+        === ↴
+        value: 42"#};
+
+    assert_eq!(error.to_string(), expected);
+    assert!(error.is_limit());
+    assert_eq!(error.error_code(), Some(11));
+
+    let error = MacroRulesError::Parse { value: "x" };
+
+    assert!(error.is_parse());
+    assert!(error.as_parse().is_some());
+}
+
+#[Error(desc = "Failed to parse input", accessors)]
+enum ParseError<'a> {
+    InvalidToken { text: &'a str, position: usize },
+    Unexpected(&'a str),
+}
+
+fn parse(input: &str) -> Result<(), ParseError<'_>> {
+    match input.find('!') {
+        Some(position) => Err(ParseError::InvalidToken {
+            text: &input[position..],
+            position,
+        }),
+        None => Err(ParseError::Unexpected(input)),
+    }
+}
+
+#[test]
+fn it_formats_fields_with_non_static_lifetime() {
+    let input = String::from("ab!c");
+    let error = parse(&input).unwrap_err();
+    let expected = indoc! {r#"
+        ParseError::InvalidToken
+        Failed to parse input
+        === ↴
+        text: !c
+        position: 2"#};
+
+    assert_eq!(error.to_string(), expected);
+    assert!(error.source().is_none());
+
+    let input = String::from("abc");
+    let error = parse(&input).unwrap_err();
+
+    assert_eq!(error.as_unexpected(), Some(&"abc"));
+}
+
+#[Error(constructor, builder)]
+struct TimeoutError {
+    #[default = std::time::Duration::from_secs(30)]
+    #[fmt(debug)]
+    timeout: std::time::Duration,
+    message: String,
+}
+
+#[test]
+fn it_fills_default_fields_in_constructor() {
+    let error = TimeoutError::new("Oh no".to_string());
+
+    assert_eq!(error.timeout, std::time::Duration::from_secs(30));
+    assert_eq!(error.message, "Oh no");
+
+    let expected = indoc! {r#"
+        TimeoutError
+        === ↴
+        timeout: 30s
+        message: Oh no"#};
+
+    assert_eq!(error.to_string(), expected);
+}
+
+#[test]
+fn it_fills_default_fields_in_builder() {
+    let error = TimeoutError::builder()
+        .with_message("Oh no".to_string())
+        .build();
+
+    assert_eq!(error.timeout, std::time::Duration::from_secs(30));
+
+    let error = TimeoutError::builder()
+        .with_timeout(std::time::Duration::from_secs(5))
+        .with_message("Oh no".to_string())
+        .build();
+
+    assert_eq!(error.timeout, std::time::Duration::from_secs(5));
+}
+
+mod framework {
+    pub trait ErrorCode {
+        fn code(&self) -> u32;
+    }
+}
+
+use framework::ErrorCode;
+
+#[Error(code = 100, impl_error_code = "framework::ErrorCode")]
+enum EnumErrorWithCodeTrait {
+    Foo,
+    #[error(code = 200)]
+    Bar {
+        a: &'static str,
+    },
+    Baz(usize),
+}
+
+#[Error(code = 300, impl_error_code = "crate::framework::ErrorCode")]
+struct StructErrorWithCodeTrait;
+
+fn codes(errors: &[&dyn ErrorCode]) -> Vec<u32> {
+    errors.iter().map(|error| error.code()).collect()
+}
+
+#[test]
+fn it_implements_error_code_trait() {
+    assert_eq!(
+        codes(&[
+            &EnumErrorWithCodeTrait::Foo,
+            &EnumErrorWithCodeTrait::Bar { a: "A" },
+            &EnumErrorWithCodeTrait::Baz(42),
+            &StructErrorWithCodeTrait,
+        ]),
+        vec![100, 200, 102, 300]
+    );
+    assert_eq!(EnumErrorWithCodeTrait::Foo.error_code(), Some(100));
+}
+
+// An item named `thiserror` takes the place of the crate, so generated code must not refer to it
+mod shadowed_thiserror {
+    pub mod thiserror {
+        pub const NAME: &str = "local";
+    }
+
+    #[Error]
+    pub enum EnumError {
+        Foo { a: usize },
+        Io(#[from] std::io::Error),
+    }
+
+    #[Error]
+    pub struct StructError(pub usize);
+}
+
+#[test]
+fn it_ignores_items_named_thiserror() {
+    let actual = format!("{}", shadowed_thiserror::EnumError::Foo { a: 42 });
+    let expected = indoc! {r#"
+        EnumError::Foo
+        === ↴
+        a: 42"#};
+
+    assert_eq!(actual, expected);
+
+    let error = shadowed_thiserror::EnumError::from(std::io::Error::other("Oh no"));
+    assert!(error.source().is_some());
+
+    let actual = format!("{}", shadowed_thiserror::StructError(42));
+    let expected = indoc! {r#"
+        StructError
+        === ↴
+        42"#};
+
+    assert_eq!(actual, expected);
+    assert_eq!(shadowed_thiserror::thiserror::NAME, "local");
+}
+
+// Without sources the type derives `thiserror::Error`, otherwise it implements it itself
+#[Error]
+struct MissingPathError(std::path::PathBuf);
+
+#[Error]
+enum PathError {
+    Read {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[test]
+fn it_formats_path_fields() {
+    let actual = format!("{}", MissingPathError("/etc/app.toml".into()));
+    let expected = indoc! {r#"
+        MissingPathError
+        === ↴
+        /etc/app.toml"#};
+
+    assert_eq!(actual, expected);
+
+    let error = PathError::Read {
+        path: "/etc/app.toml".into(),
+        source: std::io::Error::other("Oh no"),
+    };
+    let actual = format!("{}", error);
+    let expected = indoc! {r#"
+        PathError::Read
+        === ↴
+        path: /etc/app.toml
+        source: Oh no"#};
+
+    assert_eq!(actual, expected);
+}
+
+// Generated methods refer to `Option` by its full path, so local items can't shadow it
+#[allow(dead_code)]
+mod shadowed_option {
+    pub struct Option;
+    pub struct Some;
+    pub struct None;
+
+    #[Error(accessors)]
+    pub enum EnumError {
+        Foo {
+            a: usize,
+        },
+        #[error(code = 2)]
+        Bar(usize, usize),
+    }
+
+    #[Error(code = 3)]
+    pub struct StructError;
+}
+
+#[test]
+fn it_ignores_items_named_option() {
+    let error = shadowed_option::EnumError::Foo { a: 42 };
+
+    assert_eq!(error.as_foo(), Some(&42));
+    assert_eq!(error.as_bar(), None);
+    assert_eq!(error.error_code(), None);
+    assert_eq!(shadowed_option::EnumError::Bar(1, 2).error_code(), Some(2));
+    assert_eq!(shadowed_option::StructError.error_code(), Some(3));
+}
+
+// Predicates call `matches!` by its full path, so a local macro can't shadow it
+#[allow(dead_code, unused_macros)]
+mod shadowed_matches {
+    macro_rules! matches {
+        ($($tt:tt)*) => {
+            false
+        };
+    }
+
+    #[Error(predicates)]
+    pub enum EnumError {
+        Foo,
+        Bar(usize),
+    }
+}
+
+#[test]
+fn it_ignores_macros_named_matches() {
+    let error = shadowed_matches::EnumError::Foo;
+
+    assert!(error.is_foo());
+    assert!(!error.is_bar());
+}
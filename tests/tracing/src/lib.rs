@@ -0,0 +1,115 @@
+//! Checks that errors emit `tracing` events on creation with `tracing_event`.
+
+use justerror::Error;
+
+#[Error(desc = "My enum error", tracing_event)]
+pub enum EnumError {
+    Foo {
+        a: &'static str,
+        #[sensitive]
+        token: String,
+    },
+    Bar(u8, u16),
+    Baz,
+}
+
+#[Error(tracing_event)]
+pub struct StructError {
+    pub a: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+
+    // `TestWriter` prints to the test output, which can't be read back
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture(create: impl FnOnce()) -> String {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(buffer.clone())
+            .with_ansi(false)
+            .without_time()
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, create);
+
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn it_emits_event_with_named_fields() {
+        let output = capture(|| {
+            let _ = EnumError::new_foo("A", "secret".to_string());
+        });
+
+        assert!(output.contains("ERROR"));
+        assert!(output.contains("error created"));
+        assert!(output.contains("error=EnumError::Foo"));
+        assert!(output.contains("type_name=\"EnumError\""));
+        assert!(output.contains("variant=\"Foo\""));
+        assert!(output.contains("a=\"A\""));
+        assert!(output.contains("token=\"[REDACTED]\""));
+        assert!(!output.contains("secret"));
+    }
+
+    #[test]
+    fn it_emits_event_with_unnamed_fields() {
+        let output = capture(|| {
+            let _ = EnumError::new_bar(1, 2);
+        });
+
+        assert!(output.contains("variant=\"Bar\""));
+        assert!(output.contains("_0=1"));
+        assert!(output.contains("_1=2"));
+    }
+
+    #[test]
+    fn it_emits_event_without_fields() {
+        let output = capture(|| {
+            let _ = EnumError::new_baz();
+        });
+
+        assert!(output.contains("variant=\"Baz\""));
+    }
+
+    #[test]
+    fn it_emits_event_of_struct() {
+        let output = capture(|| {
+            let _ = StructError::new(42);
+        });
+
+        assert!(output.contains("type_name=\"StructError\""));
+        assert!(!output.contains("variant="));
+        assert!(output.contains("a=42"));
+    }
+}